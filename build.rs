@@ -1,17 +1,22 @@
-use fancy_regex::Regex as Fancy;
 use regex::Regex;
 use serde::Deserialize;
 use std::fmt::Write;
 use std::{env, fs, path::Path};
 
+#[path = "src/anchors.rs"]
+mod anchors;
+
 #[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 struct Data {
+    #[serde(rename(deserialize = "ID"))]
+    id: String,
     #[serde(rename(deserialize = "Name"))]
     name: String,
     #[serde(rename(deserialize = "Regex"))]
     regex: String,
-    #[serde(skip_deserializing)]
+    // derived from `regex` if missing
+    #[serde(rename(deserialize = "BoundarylessRegex"), default)]
     boundaryless: String,
     plural_name: bool,
     #[serde(rename(deserialize = "Description"))]
@@ -24,27 +29,56 @@ struct Data {
     url: Option<&'static str>,
     #[serde(rename(deserialize = "Tags"))]
     tags: Vec<&'static str>,
+    #[serde(rename(deserialize = "Examples"), default)]
+    examples: Examples,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug, Default)]
+struct Examples {
+    #[serde(rename(deserialize = "Valid"), default)]
+    valid: Vec<String>,
+    #[serde(rename(deserialize = "Invalid"), default)]
+    invalid: Vec<String>,
+}
+
+// delimiters allowed around a match in word mode, same as in src/pattern.rs
+const DELIMITERS: &str = r#"[\s"'`=:,;()\[\]{}<>]"#;
+
+fn word_bounded(boundaryless: &str) -> String {
+    format!("(?:^|{DELIMITERS})(?:{boundaryless})(?:$|{DELIMITERS})")
 }
 
 fn main() {
     let mut data: Vec<Data> = serde_json::from_str(include_str!("./src/data/regex.json")).unwrap();
 
-    data.iter_mut().for_each(|d| {
-        d.boundaryless = Fancy::new(r"(?<!\\)\^(?![^\[\]]*(?<!\\)\])")
-            .expect("can't compile for boundaryless")
-            .replace(&d.regex, "")
-            .to_string();
-        d.boundaryless = Fancy::new(r"(?<!\\)\$(?![^\[\]]*(?<!\\)\])")
-            .expect("can't compile for boundaryless")
-            .replace(&d.boundaryless, "")
-            .to_string();
+    let mut ids: Vec<&str> = data.iter().map(|d| d.id.as_str()).collect();
+    ids.sort_unstable();
+    if let Some(id) = ids.windows(2).find(|w| w[0] == w[1]) {
+        panic!("duplicate pattern ID `{}`", id[0]);
+    }
+
+    data.iter_mut()
+        .filter(|d| d.boundaryless.is_empty())
+        .for_each(|d| {
+            d.boundaryless = anchors::remove_anchors(&d.regex);
+        });
+
+    let (data, dropped): (Vec<Data>, Vec<Data>) = data.into_iter().partition(|r| {
+        Regex::new(&r.regex).is_ok()
+            && Regex::new(&r.boundaryless).is_ok()
+            && Regex::new(&word_bounded(&r.boundaryless)).is_ok()
     });
+    let dropped: Vec<&str> = dropped.iter().map(|r| r.name.as_str()).collect();
 
-    data.retain(|r| Regex::new(&r.regex).is_ok() && Regex::new(&r.boundaryless).is_ok());
+    validate_examples(&data);
 
     let mut data_str = format!("{:?}", data);
     // we want reference to [], i.e. &[]
-    data_str = data_str.replace("tags: [", "tags: &[");
+    data_str = data_str
+        .replace("tags: [", "tags: &[")
+        .replace("valid: [", "valid: &[")
+        .replace("invalid: [", "invalid: &[");
 
     let regex_str: String = data.iter().fold(String::new(), |mut output, d| {
         let _ = write!(
@@ -64,10 +98,21 @@ fn main() {
         output
     });
 
+    let word_regex_str: String = data.iter().fold(String::new(), |mut output, d| {
+        let _ = write!(
+            output,
+            r#"Lazy::new(|| Regex::new({:?}).unwrap()),"#,
+            word_bounded(&d.boundaryless)
+        );
+        output
+    });
+
     let count = data.len();
+    let dropped_count = dropped.len();
     let final_str = format!(
         r#"
-        const DATA: [Data; {count}] = {data_str};
+        static DATA: [Data; {count}] = {data_str};
+        const DROPPED: [&str; {dropped_count}] = {dropped:?};
     "#
     );
     let out_dir = env::var_os("OUT_DIR").unwrap();
@@ -78,8 +123,53 @@ fn main() {
         r#"
         static REGEX: [Lazy<Regex>; {count}] = [{regex_str}];
         static BOUNDARYLESS_REGEX: [Lazy<Regex>; {count}] = [{boundaryless_regex_str}];
+        static WORD_REGEX: [Lazy<Regex>; {count}] = [{word_regex_str}];
     "#
     );
     let regex_dest_path = Path::new(&out_dir).join("regex_data.rs");
     fs::write(regex_dest_path, final_str).unwrap();
 }
+
+// Fail if any regex doesn't behave as its examples say, and warn if boundaryless
+// regex only matches a part of a valid example, i.e. it behaves differently than the anchored one.
+fn validate_examples(data: &[Data]) {
+    let mut errors = Vec::new();
+
+    for d in data {
+        let regex = Regex::new(&d.regex).expect("dropped if invalid");
+        let boundaryless = Regex::new(&d.boundaryless).expect("dropped if invalid");
+
+        for example in &d.examples.valid {
+            if !regex.is_match(example) {
+                errors.push(format!(
+                    "{}: valid example `{example}` doesn't match",
+                    d.name
+                ));
+            }
+            match boundaryless.find(example) {
+                None => errors.push(format!(
+                    "{}: valid example `{example}` doesn't match boundaryless regex `{}`",
+                    d.name, d.boundaryless
+                )),
+                Some(m) if m.len() != example.len() && regex.is_match(example) => println!(
+                    "cargo:warning={}: boundaryless regex only matches `{}` of valid example `{example}`, consider adding `BoundarylessRegex`",
+                    d.name,
+                    m.as_str()
+                ),
+                Some(_) => {}
+            }
+        }
+        for example in &d.examples.invalid {
+            if regex.is_match(example) {
+                errors.push(format!("{}: invalid example `{example}` matches", d.name));
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        panic!(
+            "patterns don't match their examples:\n{}",
+            errors.join("\n")
+        );
+    }
+}
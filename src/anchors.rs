@@ -0,0 +1,23 @@
+//! Making boundaryless regex from anchored one, used for both built-in patterns in build.rs
+//! and loaded patterns in [`pattern`](crate::pattern).
+
+/// Remove `^` and `$` which are neither escaped nor inside a character class.
+pub(crate) fn remove_anchors(regex: &str) -> String {
+    let mut result = String::with_capacity(regex.len());
+    let mut escaped = false;
+    let mut in_class = false;
+
+    for c in regex.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '^' | '$' if !in_class => continue,
+            _ => {}
+        }
+        result.push(c);
+    }
+
+    result
+}
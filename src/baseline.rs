@@ -0,0 +1,200 @@
+/*!
+ * For accepting known findings, so that only new ones are reported.
+ *
+ * A baseline is a JSON file containing a fingerprint for every accepted [`Match`].
+ * A fingerprint is made of the name of the pattern, SHA-256 hash of the matched text
+ * and the path of the file it was found in, so secrets themselves are never written to the baseline.
+ * Paths are stored relative to the directory of the baseline file, with `/` separators,
+ * so the same file matches however it was passed and from whichever directory lemmeknow runs.
+ *
+ * Fingerprints are kept sorted, so baseline files are stable and diff-friendly.
+ *
+ * # Example:
+ *
+ * ```rust
+ * use lemmeknow::{baseline::Baseline, Identifier};
+ * let identifier = Identifier::default();
+ *
+ * let old = identifier.identify("UC11L3JDgDQMyH8iolKkVZ4w");
+ * let baseline = Baseline::from_matches(&old);
+ *
+ * let new = baseline.filter(identifier.identify("UC11L3JDgDQMyH8iolKkVZ4w"));
+ * assert!(new.is_empty());
+ * ```
+ */
+
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use std::{fs, io};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::Match;
+
+/// Version of the baseline file format.
+pub const BASELINE_VERSION: u32 = 1;
+
+/// Identifies a single finding without storing the matched text.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Fingerprint {
+    /// Name of the pattern which matched
+    pub name: String,
+    /// Hex encoded SHA-256 hash of the matched text
+    pub hash: String,
+    /// Path of the file in which the text was found, relative to the baseline file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+impl Fingerprint {
+    /// Fingerprint of the match, with its path relative to the current directory
+    pub fn new(result: &Match) -> Fingerprint {
+        Fingerprint::relative_to(result, Path::new("."))
+    }
+
+    /// Fingerprint of the match, with its path relative to `root`
+    pub fn relative_to(result: &Match, root: &Path) -> Fingerprint {
+        Fingerprint {
+            name: result.data.name.to_owned(),
+            hash: hash(&result.text),
+            path: result.path.as_deref().map(|path| relative_path(path, root)),
+        }
+    }
+}
+
+/// Set of accepted findings.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Baseline {
+    pub version: u32,
+    pub findings: BTreeSet<Fingerprint>,
+    /// Directory which paths of findings are relative to, the one containing the baseline file
+    #[serde(skip, default = "current_dir")]
+    pub root: PathBuf,
+}
+
+impl Default for Baseline {
+    fn default() -> Self {
+        Baseline {
+            version: BASELINE_VERSION,
+            findings: BTreeSet::new(),
+            root: current_dir(),
+        }
+    }
+}
+
+impl Baseline {
+    /// Create a baseline accepting all of the given matches, with paths relative to the current directory.
+    pub fn from_matches(result: &[Match]) -> Baseline {
+        Baseline::from_matches_in(result, current_dir())
+    }
+
+    /// Create a baseline accepting all of the given matches, with paths relative to `root`,
+    /// which should be the directory the baseline file is saved in.
+    pub fn from_matches_in(result: &[Match], root: impl Into<PathBuf>) -> Baseline {
+        let root = root.into();
+        Baseline {
+            findings: result
+                .iter()
+                .map(|m| Fingerprint::relative_to(m, &root))
+                .collect(),
+            root,
+            ..Default::default()
+        }
+    }
+
+    /// Returns `true` if the match is accepted by this baseline.
+    #[inline]
+    pub fn contains(&self, result: &Match) -> bool {
+        self.findings
+            .contains(&Fingerprint::relative_to(result, &self.root))
+    }
+
+    /// Only keep the matches which are not accepted by this baseline.
+    ///
+    /// # Arguments
+    ///
+    /// * result: `Vec<Match>` - matches returned by [`Identifier::identify`](crate::Identifier::identify)
+    ///
+    pub fn filter(&self, mut result: Vec<Match>) -> Vec<Match> {
+        result.retain(|m| !self.contains(m));
+        result
+    }
+
+    /// Parse baseline from JSON.
+    pub fn from_json(json: &str) -> serde_json::Result<Baseline> {
+        serde_json::from_str(json)
+    }
+
+    /// Convert baseline to prettified JSON.
+    #[inline]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Read baseline from file, paths of findings are relative to the directory containing it.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Baseline> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path)?;
+        let mut baseline = Baseline::from_json(&json)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        baseline.root = parent_dir(path);
+        Ok(baseline)
+    }
+
+    /// Write baseline to file.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut json = self.to_json();
+        json.push('\n');
+        fs::write(path, json)
+    }
+}
+
+/// Directory containing the file at `path`
+pub fn parent_dir(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_owned(),
+        _ => current_dir(),
+    }
+}
+
+fn current_dir() -> PathBuf {
+    PathBuf::from(".")
+}
+
+// `path` relative to `root` with `/` separators, or absolute if it is outside of `root`
+pub(crate) fn relative_path(path: &str, root: &Path) -> String {
+    let path = absolute(Path::new(path));
+    let root = absolute(root);
+    match path.strip_prefix(&root) {
+        Ok(relative) => relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+        Err(_) => path.to_string_lossy().replace('\\', "/"),
+    }
+}
+
+// absolute path without `.` and `..`, symbolic links are not resolved
+fn absolute(path: &Path) -> PathBuf {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_owned());
+    let mut clean = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                clean.pop();
+            }
+            component => clean.push(component),
+        }
+    }
+    clean
+}
+
+fn hash(text: &str) -> String {
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
@@ -0,0 +1,100 @@
+/*!
+ * For enumerating the built-in patterns.
+ *
+ * Everything here is re-exported from the crate root.
+ * Tags, names and IDs are compared case-insensitively.
+ *
+ * # Example:
+ *
+ * Build a tag picker:
+ *
+ * ```rust
+ * for tag in lemmeknow::all_tags() {
+ *     let count = lemmeknow::patterns_with_tag(tag).count();
+ *     println!("{tag} ({count})");
+ * }
+ *
+ * let phone = lemmeknow::pattern_by_name("phone number").unwrap();
+ * assert_eq!(phone.name, "Phone Number");
+ * assert_eq!(lemmeknow::pattern_by_id("phone-number").unwrap().name, phone.name);
+ * assert!(lemmeknow::patterns_named("github*").count() > 1);
+ * ```
+ */
+
+use crate::{Data, DATA, DROPPED};
+
+/// Built-in patterns, in the order they are tried.
+#[inline]
+pub fn patterns() -> &'static [Data] {
+    &DATA
+}
+
+/// Built-in pattern having the given name.
+pub fn pattern_by_name(name: &str) -> Option<&'static Data> {
+    DATA.iter()
+        .find(|data| data.name.eq_ignore_ascii_case(name))
+}
+
+/// Built-in pattern having the given ID.
+pub fn pattern_by_id(id: &str) -> Option<&'static Data> {
+    DATA.iter().find(|data| data.id.eq_ignore_ascii_case(id))
+}
+
+/// Built-in patterns whose name matches the glob, where `*` matches any text and `?` any character.
+pub fn patterns_named(glob: &str) -> impl Iterator<Item = &'static Data> + '_ {
+    DATA.iter()
+        .filter(move |data| matches_glob(glob, data.name))
+}
+
+/// Built-in patterns having the given tag.
+pub fn patterns_with_tag(tag: &str) -> impl Iterator<Item = &'static Data> + '_ {
+    DATA.iter()
+        .filter(move |data| data.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+}
+
+/// Tags of all built-in patterns, sorted and without duplicates.
+pub fn all_tags() -> Vec<&'static str> {
+    let mut tags: Vec<&'static str> = DATA.iter().flat_map(|data| data.tags).copied().collect();
+    tags.sort_unstable();
+    tags.dedup();
+    tags
+}
+
+/// Names of the built-in patterns which are not available,
+/// because their regex isn't supported by the `regex` crate.
+#[inline]
+pub fn dropped_patterns() -> &'static [&'static str] {
+    &DROPPED
+}
+
+// case-insensitive glob matching supporting only `*` and `?`
+pub(crate) fn matches_glob(glob: &str, text: &str) -> bool {
+    let glob: Vec<char> = glob.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut g, mut t) = (0, 0);
+    // position after the last `*` and the text position it was tried at
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match glob.get(g) {
+            Some('*') => {
+                g += 1;
+                backtrack = Some((g, t));
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                g += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((bg, bt)) => {
+                    g = bg;
+                    t = bt + 1;
+                    backtrack = Some((bg, bt + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    glob[g..].iter().all(|&c| c == '*')
+}
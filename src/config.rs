@@ -0,0 +1,297 @@
+/*!
+ * For reading default scan settings from `lemmeknow.toml`.
+ *
+ * > This requires `cli` feature to be enabled. (enabled by default)
+ *
+ * ```toml
+ * min_rarity = 0.3
+ * max_rarity = 1.0
+ * include = ["Credentials"]
+ * exclude = ["Identifiers"]
+ * # whether matches need `any` or `all` of the included tags
+ * tag_match = "any"
+ * # see `tags` module
+ * tag_expr = "Credentials & !Bug Bounty"
+ * # patterns by ID, or by name where `*` matches any text
+ * exclude_ids = ["phone-number"]
+ * exclude_names = ["Date of*"]
+ * # `anchored`, `word` or `boundaryless`, `boundaryless = true/false` works too
+ * mode = "word"
+ * # pattern files, relative to this file
+ * patterns = ["patterns/internal.json"]
+ * # threads to scan files with, 1 scans sequentially, 0 uses one per CPU
+ * jobs = 4
+ * # stop scanning once 1000 matches are found or after 30 seconds
+ * max_matches = 1000
+ * deadline = 30
+ * # binary files are scanned for printable strings by default, or "skip" / "full"
+ * binary = "skip"
+ * # files larger than this many bytes are skipped
+ * max_filesize = 104857600
+ * # refang defanged indicators, e.g. `hxxps://evil[.]com`, before matching
+ * refang = true
+ * # findings in files matching these globs, relative to this file, are not reported
+ * ignore = ["*.lock", "tests/fixtures"]
+ * format = "json"
+ * verbose = false
+ * # use this profile unless another one is passed with `--profile`
+ * profile = "audit"
+ *
+ * # steps applied to text before matching, none are enabled by default
+ * [normalize]
+ * strip_wrapping = true
+ * remove_invisible = true
+ * nfkc = true
+ * join_lines = true
+ *
+ * # change or disable built-in patterns by ID or name
+ * [overrides.phone-number]
+ * rarity = 0.2
+ * tags = ["Identifiers", "PII"]
+ * [overrides."Key:Value Pair"]
+ * disabled = true
+ *
+ * # profiles can be defined along with the built-in ones, see `profile` module
+ * [profiles.audit]
+ * min_rarity = 0.5
+ * exclude = ["Bug Bounty"]
+ * ```
+ *
+ * Settings missing from the file or a profile have the same values as the command line defaults.
+ *
+ * # Example:
+ *
+ * ```rust
+ * use lemmeknow::config::{Config, Format};
+ * let config = Config::from_toml("include = [\"Credentials\"]\nformat = \"json\"").unwrap();
+ *
+ * assert_eq!(config.identifier.tags, ["Credentials"]);
+ * assert_eq!(config.identifier.min_rarity, 0.1);
+ * assert_eq!(config.format, Format::Json);
+ * ```
+ */
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io, mem};
+
+use serde::Deserialize;
+
+use crate::baseline;
+use crate::profile::Profile;
+use crate::Identifier;
+
+/// Name of the config file which is looked up.
+pub const CONFIG_FILE: &str = "lemmeknow.toml";
+
+// same as the defaults of command line options
+const DEFAULTS: &str = r#"
+min_rarity = 0.1
+mode = "boundaryless"
+file_support = true
+"#;
+
+/// How the output shall be printed
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    #[default]
+    Table,
+    Json,
+}
+
+/// Default scan settings.
+#[derive(Deserialize)]
+pub struct Config {
+    #[serde(flatten)]
+    pub identifier: Identifier,
+    /// Files containing extra patterns, see [`pattern`](crate::pattern)
+    #[serde(default)]
+    pub patterns: Vec<PathBuf>,
+    /// Glob patterns of paths whose findings are not reported, relative to `root`
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// Directory of the config file, set by [`Config::load`].
+    /// Paths are matched against `ignore` as they are if it's `None`.
+    #[serde(skip)]
+    pub root: Option<PathBuf>,
+    #[serde(default)]
+    pub format: Format,
+    #[serde(default)]
+    pub verbose: bool,
+    /// Name of the profile to use by default
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// User defined profiles, by name
+    #[serde(skip)]
+    pub profiles: HashMap<String, Identifier>,
+    /// Keys which aren't settings, e.g. misspelled ones, along with the table they are in
+    #[serde(skip)]
+    pub unknown_keys: Vec<String>,
+    // profiles as they are written, turned into `profiles` by `from_toml`
+    #[serde(default, rename = "profiles")]
+    profile_tables: HashMap<String, toml::Table>,
+    #[serde(flatten)]
+    unknown: toml::Table,
+}
+
+/// Settings of an identifier, along with the keys which aren't settings
+#[derive(Deserialize)]
+struct Settings {
+    #[serde(flatten)]
+    identifier: Identifier,
+    #[serde(flatten)]
+    unknown: toml::Table,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::from_toml("").expect("defaults are valid")
+    }
+}
+
+impl Config {
+    /// Parse config from TOML.
+    ///
+    /// Keys which aren't settings are listed in `unknown_keys`, e.g. `min_rarty` or `profiles.audit.exclude_tag`.
+    pub fn from_toml(toml: &str) -> Result<Config, toml::de::Error> {
+        let mut config: Config = with_defaults(toml::from_str(toml)?).try_into()?;
+        config.unknown_keys = mem::take(&mut config.unknown)
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+
+        for (name, table) in mem::take(&mut config.profile_tables) {
+            let settings: Settings = with_defaults(table).try_into()?;
+            config.unknown_keys.extend(
+                settings
+                    .unknown
+                    .into_iter()
+                    .map(|(key, _)| format!("profiles.{name}.{key}")),
+            );
+            config.profiles.insert(name, settings.identifier);
+        }
+        config.unknown_keys.sort();
+
+        Ok(config)
+    }
+
+    /// Use settings of the profile named `name`.
+    ///
+    /// User defined profiles take precedence over the built-in ones.
+    /// Rarity range, tags and match mode are replaced by the ones from the profile,
+    /// while overrides of the profile are applied along with the ones from the config,
+    /// and so are patterns included or excluded by ID or name, e.g. `exclude_ids`.
+    /// Patterns, `file_support`, `jobs`, limits, `binary`, `max_filesize`, `refang` and `normalize` of the config are kept.
+    pub fn use_profile(&mut self, name: &str) -> Result<(), String> {
+        let mut profile = match self.profiles.remove(name) {
+            Some(profile) => profile,
+            None => name.parse::<Profile>()?.identifier(),
+        };
+
+        let file_support = self.identifier.file_support;
+        let Identifier {
+            max_matches,
+            max_matches_per_pattern,
+            max_input_size,
+            deadline,
+            binary,
+            max_filesize,
+            refang,
+            normalize,
+            ..
+        } = self.identifier;
+        let parallelism = mem::take(&mut self.identifier.parallelism);
+        let patterns = mem::take(&mut self.identifier.patterns);
+        let mut overrides = mem::take(&mut self.identifier.overrides);
+        overrides.extend(profile.overrides.drain());
+        for (ids, kept) in [
+            (&mut profile.ids, &mut self.identifier.ids),
+            (&mut profile.names, &mut self.identifier.names),
+            (&mut profile.exclude_ids, &mut self.identifier.exclude_ids),
+            (
+                &mut profile.exclude_names,
+                &mut self.identifier.exclude_names,
+            ),
+        ] {
+            ids.splice(0..0, mem::take(kept));
+        }
+        self.identifier = profile.file_support(file_support).patterns(patterns);
+        self.identifier.overrides = overrides;
+        self.identifier.parallelism = parallelism;
+        self.identifier.max_matches = max_matches;
+        self.identifier.max_matches_per_pattern = max_matches_per_pattern;
+        self.identifier.max_input_size = max_input_size;
+        self.identifier.deadline = deadline;
+        self.identifier.binary = binary;
+        self.identifier.max_filesize = max_filesize;
+        self.identifier.refang = refang;
+        self.identifier.normalize = normalize;
+        Ok(())
+    }
+
+    /// Read config from file.
+    ///
+    /// Paths of pattern files are resolved relative to the directory containing the config file.
+    /// Unknown keys are an error of [`io::ErrorKind::InvalidInput`] kind, naming them.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Config> {
+        let path = path.as_ref();
+        let toml = fs::read_to_string(path)?;
+        let mut config =
+            Config::from_toml(&toml).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if !config.unknown_keys.is_empty() {
+            let keys: Vec<String> = config
+                .unknown_keys
+                .iter()
+                .map(|key| format!("`{key}`"))
+                .collect();
+            let message = format!("unknown key(s) {}", keys.join(", "));
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+
+        if let Some(dir) = path.parent() {
+            config.patterns = config.patterns.iter().map(|p| dir.join(p)).collect();
+        }
+        config.root = Some(baseline::parent_dir(path));
+
+        Ok(config)
+    }
+
+    /// Find `lemmeknow.toml` in the current directory or any of its parents.
+    pub fn discover() -> Option<PathBuf> {
+        let cwd = env::current_dir().ok()?;
+        cwd.ancestors()
+            .map(|dir| dir.join(CONFIG_FILE))
+            .find(|path| path.is_file())
+    }
+
+    /// Returns `true` if findings in the file at `path` shall not be reported,
+    /// i.e. the path or any of its parent directories match one of the `ignore` globs.
+    ///
+    /// Paths are made relative to `root` first, so globs match the same files from any directory.
+    /// Invalid glob patterns never match.
+    pub fn is_ignored(&self, path: &str) -> bool {
+        let relative;
+        let path = match &self.root {
+            Some(root) => {
+                relative = baseline::relative_path(path, root);
+                Path::new(&relative)
+            }
+            None => Path::new(path.strip_prefix("./").unwrap_or(path)),
+        };
+        self.ignore
+            .iter()
+            .filter_map(|pattern| glob::Pattern::new(pattern).ok())
+            .any(|pattern| path.ancestors().any(|p| pattern.matches_path(p)))
+    }
+}
+
+fn with_defaults(table: toml::Table) -> toml::Table {
+    let mut defaults: toml::Table = toml::from_str(DEFAULTS).expect("defaults are valid");
+    // `boundaryless` is an alias of `mode`
+    if table.contains_key("boundaryless") {
+        defaults.remove("mode");
+    }
+    defaults.extend(table);
+    defaults
+}
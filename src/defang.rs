@@ -0,0 +1,201 @@
+/*!
+ * For refanging indicators written defanged, and defanging them for reports.
+ *
+ * Threat-intel reports write indicators defanged so they can't be clicked,
+ * e.g. `hxxps://evil[.]com`, `1.2.3[.]4` or `user[at]example.com`.
+ * An identifier with `refang` enabled refangs such text before matching, see [`refang`] for the styles
+ * it understands, and keeps the text as it was written in `original` of the match.
+ *
+ * # Example:
+ *
+ * ```rust
+ * use lemmeknow::{defang, Identifier};
+ * let identifier = Identifier::default()
+ *     .include_ids(&["url".to_owned()])
+ *     .refang(true);
+ * let result = identifier.identify("hxxps://evil[.]com/login");
+ * assert_eq!(result[0].text, "https://evil.com/login");
+ * assert_eq!(result[0].original.as_deref(), Some("hxxps://evil[.]com/login"));
+ *
+ * assert_eq!(defang::defang(&result[0].text), "hxxps[://]evil[.]com/login");
+ * ```
+ */
+
+use std::borrow::Cow;
+
+use crate::{Data, Match};
+
+// defanged forms of separators, matched ignoring ascii case
+const SEPARATORS: &[(&str, &str)] = &[
+    ("[.]", "."),
+    ("{.}", "."),
+    ("[dot]", "."),
+    ("(dot)", "."),
+    ("{dot}", "."),
+    ("[://]", "://"),
+    ("[:]", ":"),
+    ("[/]", "/"),
+    ("[@]", "@"),
+    ("[at]", "@"),
+    ("(at)", "@"),
+    ("{at}", "@"),
+];
+
+// defanged schemes, only at the start of a word and followed by `s`, `:` or `[`
+const SCHEMES: &[(&str, &str)] = &[("hxxp", "http"), ("fxp", "ftp")];
+
+/// Tags of the patterns whose matches are defanged by [`defang_match`]
+const DEFANGED_TAGS: &[&str] = &["URL", "IP", "Networking", "Email"];
+
+/// Refang text, e.g. `hxxps://evil[.]com` becomes `https://evil.com`.
+///
+/// Understood styles, ignoring ascii case:
+/// * `hxxp`, `hxxps` and `fxp` schemes
+/// * `[.]`, `{.}`, `[dot]`, `(dot)` and `{dot}` for `.`
+/// * `[://]`, `[:]` and `[/]` for `://`, `:` and `/`
+/// * `[@]`, `[at]`, `(at)` and `{at}` for `@`
+///
+/// `\.` and `(.)` are left as they are, since they are regular expressions more often than defanged text.
+/// Returns the text as it is if it isn't defanged.
+///
+/// ```
+/// use lemmeknow::defang::refang;
+/// assert_eq!(refang("1.2.3[.]4"), "1.2.3.4");
+/// assert_eq!(refang("user[AT]example{.}com"), "user@example.com");
+/// assert_eq!(refang(r"evil\.com"), r"evil\.com");
+/// assert_eq!(refang("hXXp[://]example.com"), "http://example.com");
+/// ```
+pub fn refang(text: &str) -> Cow<'_, str> {
+    match refang_bytes(text.as_bytes()) {
+        Cow::Borrowed(_) => Cow::Borrowed(text),
+        Cow::Owned(bytes) => Cow::Owned(
+            String::from_utf8(bytes).expect("only ascii is replaced, at ascii characters"),
+        ),
+    }
+}
+
+/// Same as [`refang`], for bytes.
+pub fn refang_bytes(text: &[u8]) -> Cow<'_, [u8]> {
+    let mut refanged: Option<Vec<u8>> = None;
+    let mut i = 0;
+
+    while i < text.len() {
+        let rest = &text[i..];
+        let replacement = SEPARATORS
+            .iter()
+            .find(|(from, _)| starts_with_ignore_case(rest, from))
+            .or_else(|| {
+                let word_start = i == 0 || !text[i - 1].is_ascii_alphanumeric();
+                SCHEMES.iter().find(|(from, _)| {
+                    word_start
+                        && starts_with_ignore_case(rest, from)
+                        && is_scheme_end(&rest[from.len()..])
+                })
+            });
+
+        match replacement {
+            Some((from, to)) => {
+                let refanged = refanged.get_or_insert_with(|| text[..i].to_vec());
+                refanged.extend_from_slice(to.as_bytes());
+                i += from.len();
+            }
+            None => {
+                if let Some(refanged) = &mut refanged {
+                    refanged.push(text[i]);
+                }
+                i += 1;
+            }
+        }
+    }
+
+    match refanged {
+        Some(refanged) => Cow::Owned(refanged),
+        None => Cow::Borrowed(text),
+    }
+}
+
+/// Defang text so it can't be clicked, e.g. `https://evil.com` becomes `hxxps[://]evil[.]com`.
+///
+/// Schemes `http` and `ftp` become `hxxp` and `fxp`, `://` becomes `[://]`, `.` becomes `[.]`
+/// and `@` becomes `[at]`, which [`refang`] turns back.
+///
+/// ```
+/// use lemmeknow::defang::{defang, refang};
+/// assert_eq!(defang("1.2.3.4"), "1[.]2[.]3[.]4");
+/// assert_eq!(defang("user@example.com"), "user[at]example[.]com");
+/// assert_eq!(refang(&defang("http://example.com")), "http://example.com");
+/// ```
+pub fn defang(text: &str) -> String {
+    let mut defanged = String::with_capacity(text.len() + 8);
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        let word_start = defanged
+            .chars()
+            .next_back()
+            .is_none_or(|c| !c.is_ascii_alphanumeric());
+        let scheme = SCHEMES.iter().find(|(_, from)| {
+            word_start
+                && starts_with_ignore_case(rest.as_bytes(), from)
+                && is_scheme_end(&rest.as_bytes()[from.len()..])
+        });
+
+        if let Some((to, from)) = scheme {
+            // letters which differ become `x`, keeping their case, e.g. `HTTP` becomes `HXXP`
+            for (c, x) in rest[..from.len()].chars().zip(to.chars()) {
+                defanged.push(match c {
+                    c if c.eq_ignore_ascii_case(&x) => c,
+                    c if c.is_ascii_uppercase() => 'X',
+                    _ => 'x',
+                });
+            }
+            rest = &rest[from.len()..];
+        } else if let Some(after) = rest.strip_prefix("://") {
+            defanged.push_str("[://]");
+            rest = after;
+        } else {
+            match c {
+                '.' => defanged.push_str("[.]"),
+                '@' => defanged.push_str("[at]"),
+                c => defanged.push(c),
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    defanged
+}
+
+/// Whether matches of the pattern are defanged in reports, i.e. it has a `URL`, `IP`, `Networking` or `Email` tag.
+pub fn should_defang(data: &Data) -> bool {
+    data.tags
+        .iter()
+        .any(|tag| DEFANGED_TAGS.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+}
+
+/// Defang the text of a match, if it is of a pattern for which [`should_defang`] is `true`.
+///
+/// `original` is defanged too, as it may be defanged only partly, e.g. `hxxps://evil.com[/]login`.
+/// It is refanged first, so it is defanged the same way as the text.
+pub fn defang_match(result: &mut Match) {
+    if should_defang(&result.data) {
+        result.text = defang(&result.text);
+        if let Some(original) = &mut result.original {
+            *original = defang(&refang(original));
+        }
+    }
+}
+
+fn starts_with_ignore_case(text: &[u8], prefix: &str) -> bool {
+    text.get(..prefix.len())
+        .is_some_and(|start| start.eq_ignore_ascii_case(prefix.as_bytes()))
+}
+
+// `s`, `:` or `[` following a defanged scheme
+fn is_scheme_end(rest: &[u8]) -> bool {
+    let rest = match rest.first() {
+        Some(b's' | b'S') => &rest[1..],
+        _ => rest,
+    };
+    matches!(rest.first(), Some(b':' | b'['))
+}
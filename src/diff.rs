@@ -0,0 +1,139 @@
+/*!
+ * For scanning only the lines added by a unified diff.
+ *
+ * This is useful in pre-commit hooks and PR checks, where only newly introduced text matters.
+ * Works with the output of `git diff` as well as `diff -u`.
+ *
+ * # Example:
+ *
+ * ```rust
+ * use lemmeknow::diff;
+ * let patch = "\
+ * --- a/config.py
+ * +++ b/config.py
+ * @@ -1,2 +1,2 @@
+ *  host = 'localhost'
+ * -port = 8080
+ * +port = 8888888888
+ * ";
+ *
+ * let added = diff::added_lines(patch);
+ * assert_eq!(added.len(), 1);
+ * assert_eq!(added[0].path, "config.py");
+ * assert_eq!(added[0].line, 2);
+ * assert_eq!(added[0].content, "port = 8888888888");
+ * ```
+ */
+
+/// A line added by a diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddedLine<'a> {
+    /// Path of the file in the new version
+    pub path: &'a str,
+    /// Line number (starting from 1) in the new version of the file
+    pub line: usize,
+    /// Content of the line, without the leading `+`
+    pub content: &'a str,
+}
+
+/// Parse unified diff and return all the added lines.
+///
+/// Lines of deleted files are ignored, as well as anything which isn't a part of a hunk.
+pub fn added_lines(diff: &str) -> Vec<AddedLine<'_>> {
+    hunk_lines(diff, false)
+}
+
+/// Parse unified diff and return the lines of the new version of files which it shows,
+/// i.e. added lines along with the context lines around them.
+pub fn new_lines(diff: &str) -> Vec<AddedLine<'_>> {
+    hunk_lines(diff, true)
+}
+
+// added lines, along with context lines if `context` is `true`
+fn hunk_lines(diff: &str, context: bool) -> Vec<AddedLine<'_>> {
+    let mut added = Vec::new();
+    let mut path: Option<&str> = None;
+    // (line number in new file, old lines remaining, new lines remaining) for the current hunk
+    let mut hunk: Option<(usize, usize, usize)> = None;
+
+    for line in diff.lines() {
+        if let Some((next, old, new)) = hunk.as_mut().filter(|(_, old, new)| *old + *new > 0) {
+            match line.as_bytes().first() {
+                Some(b'+') => {
+                    if let Some(path) = path {
+                        added.push(AddedLine {
+                            path,
+                            line: *next,
+                            content: &line[1..],
+                        });
+                    }
+                    *next += 1;
+                    *new = new.saturating_sub(1);
+                }
+                Some(b'-') => *old = old.saturating_sub(1),
+                // "\ No newline at end of file"
+                Some(b'\\') => {}
+                // context line, an empty one may have lost its leading space
+                Some(b' ') | None => {
+                    if let Some(path) = path.filter(|_| context) {
+                        added.push(AddedLine {
+                            path,
+                            line: *next,
+                            content: line.get(1..).unwrap_or_default(),
+                        });
+                    }
+                    *next += 1;
+                    *old = old.saturating_sub(1);
+                    *new = new.saturating_sub(1);
+                }
+                _ => hunk = None,
+            }
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix("@@ ") {
+            hunk = parse_hunk_header(header);
+        } else if let Some(new_path) = line.strip_prefix("+++ ") {
+            path = parse_path(new_path);
+        } else if line.starts_with("diff ") {
+            path = None;
+            hunk = None;
+        }
+    }
+
+    added
+}
+
+// parse "-l,s +l,s @@ ..." into (start of new, length of old, length of new)
+fn parse_hunk_header(header: &str) -> Option<(usize, usize, usize)> {
+    let mut ranges = header.split_whitespace();
+    let old = ranges.next()?.strip_prefix('-')?;
+    let new = ranges.next()?.strip_prefix('+')?;
+
+    // length is omitted when it is 1
+    let range = |r: &str| -> Option<(usize, usize)> {
+        match r.split_once(',') {
+            Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+            None => Some((r.parse().ok()?, 1)),
+        }
+    };
+
+    let (_, old_len) = range(old)?;
+    let (new_start, new_len) = range(new)?;
+    Some((new_start, old_len, new_len))
+}
+
+// "b/src/main.rs\t2022-10-19 ..." -> "src/main.rs", "/dev/null" -> None
+fn parse_path(path: &str) -> Option<&str> {
+    let path = path.split('\t').next().unwrap_or(path).trim_end();
+    let path = path
+        .strip_prefix('"')
+        .and_then(|p| p.strip_suffix('"'))
+        .unwrap_or(path);
+
+    if path == "/dev/null" {
+        None
+    } else {
+        Some(path.strip_prefix("b/").unwrap_or(path))
+    }
+}
@@ -1,249 +1,188 @@
 //! For identifying bytes
 
+use std::borrow::Cow;
+use std::ops::Range;
+
 use once_cell::sync::Lazy;
 use regex::bytes::Regex;
 use serde::Serialize;
 
+use super::{Engine, Haystack, MatchRef};
+use crate::normalize::{Normalize, Normalized};
+use crate::pattern::Pattern;
 use crate::Data;
-use crate::DATA;
+use crate::MatchMode;
 
 // this is REGEX_DATA and BOUNDARYLESS_REGEX_DATA
 include!(concat!(env!("OUT_DIR"), "/regex_data.rs"));
 
-pub struct Identifier {
-    /// Keep Data having minimum Rarity of supplied `min_rarity`
-    pub min_rarity: f32,
-    /// Keep Data having maximum Rarity of supplied `max_rarity`
-    pub max_rarity: f32,
-    /// Only include the Data which have at least one of the specified `tags`
-    pub tags: Vec<String>,
-    /// Only include Data which doesn't have any of the `excluded_tags`
-    pub exclude_tags: Vec<String>,
-    /// Use boundaryless regex
-    pub boundaryless: bool,
-    /// Scan files having supplied text as filename
-    pub file_support: bool,
+/// Identifier for bytes, see [`Engine`] for its configuration and methods.
+///
+/// ```
+/// use lemmeknow::bytes::Identifier;
+/// let identifier = Identifier::default().include_ids(&["phone-number".to_owned()]);
+/// let result = identifier.identify(b"8888888888");
+/// assert_eq!(result[0].text, b"8888888888");
+/// ```
+pub type Identifier = Engine<[u8]>;
+
+/// structure containing the bytes and it's possible identification.
+#[derive(Serialize, Debug)]
+#[non_exhaustive]
+pub struct Match {
+    pub text: Vec<u8>,
+    pub data: Data,
+    /// Path of the file in which `text` was found, if it came from a file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Line number (starting from 1) at which `text` was found, if it came from a file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// Offset in bytes at which `text` was found, if it came from a file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    /// Text as it was written at `offset`, if it was normalized or refanged before matching,
+    /// see [`normalize`](crate::normalize) and [`defang`](crate::defang)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original: Option<Vec<u8>>,
 }
 
-impl Identifier {
-    #[inline]
-    pub fn min_rarity(mut self, rarity: f32) -> Self {
-        self.min_rarity = rarity;
-        self
+impl Match {
+    pub fn new(text: Vec<u8>, data: Data) -> Match {
+        Match {
+            text,
+            data,
+            path: None,
+            line: None,
+            offset: None,
+            original: None,
+        }
     }
 
+    /// Set the path of the file in which `text` was found
     #[inline]
-    pub fn max_rarity(mut self, rarity: f32) -> Self {
-        self.max_rarity = rarity;
+    pub fn with_path(mut self, path: &str) -> Match {
+        self.path = Some(path.to_owned());
         self
     }
 
+    /// Set the line number at which `text` was found
     #[inline]
-    pub fn include_tags(mut self, tags: &[String]) -> Self {
-        self.tags.extend_from_slice(tags);
+    pub fn with_line(mut self, line: usize) -> Match {
+        self.line = Some(line);
         self
     }
 
+    /// Set the offset in bytes at which `text` was found
     #[inline]
-    pub fn exclude_tags(mut self, tags: &[String]) -> Self {
-        self.exclude_tags.extend_from_slice(tags);
+    pub fn with_offset(mut self, offset: usize) -> Match {
+        self.offset = Some(offset);
         self
     }
 
+    /// Set the text as it was written, before it was normalized or refanged
     #[inline]
-    pub fn boundaryless(mut self, boundaryless: bool) -> Self {
-        self.boundaryless = boundaryless;
+    pub fn with_original(mut self, original: &[u8]) -> Match {
+        self.original = Some(original.to_owned());
         self
     }
+}
 
+impl From<MatchRef<'_, [u8]>> for Match {
     #[inline]
-    pub fn file_support(mut self, support: bool) -> Self {
-        self.file_support = support;
-        self
+    fn from(result: MatchRef<'_, [u8]>) -> Self {
+        result.into_owned()
     }
 }
 
-impl Default for Identifier {
-    fn default() -> Self {
-        Identifier {
-            min_rarity: 0.0,
-            max_rarity: 1.0,
-            tags: vec![],
-            exclude_tags: vec![],
-            boundaryless: false,
-            file_support: false,
+impl Haystack for [u8] {
+    type Regex = Regex;
+    type Match = Match;
+
+    #[inline]
+    fn builtin(mode: MatchMode) -> &'static [Lazy<Regex>] {
+        match mode {
+            MatchMode::Anchored => &REGEX,
+            MatchMode::Word => &WORD_REGEX,
+            MatchMode::Boundaryless => &BOUNDARYLESS_REGEX,
         }
     }
-}
 
-/// structure containing the bytes and it's possible identification.
-#[derive(Serialize, Debug)]
-pub struct Match {
-    pub text: Vec<u8>,
-    pub data: Data,
-}
+    #[inline]
+    fn pattern_regex(pattern: &Pattern, mode: MatchMode) -> &Regex {
+        pattern.bytes_regex(mode)
+    }
 
-impl Match {
-    pub fn new(text: Vec<u8>, data: Data) -> Match {
-        Match { text, data }
+    #[inline]
+    fn is_match(regex: &Regex, text: &[u8]) -> bool {
+        regex.is_match(text)
     }
-}
 
-#[cfg(not(target_arch = "wasm32"))]
-impl Identifier {
-    /// Identify the given bytes.
-    ///
-    /// Finds all possible identifications.
-    ///
-    /// # Arguments
-    ///
-    /// * text: &[u8] - text which we want to identify
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// let identifier = lemmeknow::bytes::Identifier::default();
-    /// let text = b"UC11L3JDgDQMyH8iolKkVZ4w";
-    /// let result = identifier.identify(text);
-    /// assert_eq!(result[0].data.name, "YouTube Channel ID");
-    /// ```
-    ///
-    pub fn identify(&self, text: &[u8]) -> Vec<Match> {
-        let regexes = if self.boundaryless {
-            &BOUNDARYLESS_REGEX
-        } else {
-            &REGEX
-        };
-        DATA.iter()
-            .enumerate()
-            .filter_map(|(i, e)| {
-                if is_valid_filter(self, e) && regexes[i].is_match(text) {
-                    Some(Match::new(text.to_owned(), e.clone()))
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<Match>>()
-    }
-
-    /// This returns the first identification.
-    ///
-    /// Due to how data is stored, this means that the returned result has the highest `rarity`.
-    ///
-    /// # Arguments
-    ///
-    /// * text: &[u8] - text which we want to identify
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// let identifier = lemmeknow::bytes::Identifier::default();
-    /// let some_result = identifier.first_match(b"8888888888");
-    /// let not_gonna_find = identifier.first_match(b"a friend for swanandx");
-    ///  
-    /// assert_eq!(some_result.unwrap().data.name, "Phone Number");
-    /// assert!(not_gonna_find.is_none());
-    /// ```
-    ///
-    pub fn first_match(&self, text: &[u8]) -> Option<Match> {
-        let regexes = if self.boundaryless {
-            &BOUNDARYLESS_REGEX
-        } else {
-            &REGEX
-        };
-
-        for (i, x) in DATA
-            .iter()
-            .enumerate()
-            .filter(|(_, x)| is_valid_filter(self, x))
-        {
-            // only consider the regex which compiles!
-            if regexes[i].is_match(text) {
-                return Some(Match::new(text.to_owned(), x.clone()));
-            }
+    #[inline]
+    fn find_len(regex: &Regex, text: &[u8]) -> Option<usize> {
+        regex.find(text).map(|m| m.len())
+    }
+
+    #[inline]
+    fn byte_len(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn into_match(result: MatchRef<'_, [u8]>) -> Match {
+        Match {
+            text: result.text.to_owned(),
+            data: result.data.clone(),
+            path: result.path.map(str::to_owned),
+            line: result.line,
+            offset: result.offset,
+            original: result.original.map(ToOwned::to_owned),
         }
+    }
 
-        None
+    #[inline]
+    fn match_parts(result: &Match) -> (&[u8], &Data) {
+        (&result.text, &result.data)
     }
-}
 
-// Output Implementation
-impl Identifier {
-    /// Convert [`Vec<Match>`] to JSON
-    ///
-    /// Returns prettified JSON string.
-    ///
-    /// Helpful if you want to convert possible identifications to JSON
-    /// for using in web APIs or something else.
-    ///
-    /// # Arguments
-    ///
-    /// * result: &[Match] - Reference to `Vec<Match>`.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use lemmeknow::Identifier;
-    /// let identifier = Identifier::default();
-    /// let result = identifier.identify("UC11L3JDgDQMyH8iolKkVZ4w");
-    /// let result_in_json = Identifier::to_json(&result);
-    /// println!("{result_in_json}");
-    /// ```
-    ///
-    #[inline]
-    pub fn to_json(result: &[Match]) -> String {
-        serde_json::to_string_pretty(result).unwrap_or_default()
+    #[inline]
+    fn truncated(&self, len: usize) -> &[u8] {
+        &self[..len.min(self.len())]
     }
-}
 
-// Identifier implementation for wasm
-#[cfg(target_arch = "wasm32")]
-impl Identifier {
-    // There is no file system on the web, so we are not reading strings from file.
-    // let the user perform the I/O and read the file, then pass the content of it.
-    pub fn identify(&self, text: &[Vec<u8>]) -> Vec<Match> {
-        let regexes = if self.boundaryless {
-            &BOUNDARYLESS_REGEX
-        } else {
-            &REGEX
-        };
-
-        text.iter()
-            .flat_map(|text| {
-                DATA.iter().enumerate().filter_map(|(i, e)| {
-                    if is_valid_filter(self, e) && regexes[i].is_match(text) {
-                        Some(Match::new(text.to_owned(), e.clone()))
-                    } else {
-                        None
-                    }
-                })
-            })
-            .collect()
+    #[inline]
+    fn slice(&self, range: Range<usize>) -> &[u8] {
+        &self[range]
     }
-}
 
-fn is_valid_filter(configs: &Identifier, regex_data: &Data) -> bool {
-    if regex_data.rarity < configs.min_rarity {
-        return false;
+    #[inline]
+    fn is_string_byte(byte: u8) -> bool {
+        byte != b' ' && !byte.is_ascii_control()
     }
-    if regex_data.rarity > configs.max_rarity {
-        return false;
+
+    #[inline]
+    fn from_string_bytes(bytes: &[u8]) -> &[u8] {
+        bytes
     }
 
-    if configs
-        .tags
-        .iter()
-        .any(|y| !regex_data.tags.iter().any(|x| x == y))
-    {
-        return false;
+    #[inline]
+    fn file_name(&self) -> Option<&str> {
+        std::str::from_utf8(self).ok()
     }
-    if configs
-        .exclude_tags
-        .iter()
-        .any(|y| regex_data.tags.iter().any(|x| x == y))
-    {
-        return false;
+
+    #[inline]
+    fn normalized(&self, normalize: &Normalize, refang: bool) -> Normalized<'_, [u8]> {
+        let mut normalized = normalize.apply_bytes(self);
+        if refang {
+            if let Cow::Owned(refanged) = crate::defang::refang_bytes(&normalized.text) {
+                normalized.text = Cow::Owned(refanged);
+            }
+        }
+        normalized
     }
 
-    true
+    #[inline]
+    fn texts(string: &[u8]) -> impl Iterator<Item = (usize, &[u8])> {
+        std::iter::once((0, string))
+    }
 }
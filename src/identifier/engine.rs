@@ -0,0 +1,1050 @@
+//! Generic engine behind [`Identifier`](super::Identifier) and [`bytes::Identifier`](super::bytes::Identifier)
+
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::io;
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    super::scan::{self, Extract},
+    crate::limits::Budget,
+    crate::sniff::{sniff, Binary, SkipReason},
+    crate::stats::ScanStats,
+    std::io::Read,
+    std::path::Path,
+};
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+use {rayon::ThreadPool, std::sync::Arc};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::{MatchMode, MatchRef};
+use crate::catalogue::matches_glob;
+use crate::normalize::{Normalize, Normalized};
+use crate::parallel::Parallelism;
+use crate::pattern::{Override, Pattern};
+use crate::rank::Rank;
+use crate::sniff::BinaryPolicy;
+use crate::tags::{TagExpr, TagMatch};
+use crate::Data;
+use crate::DATA;
+
+/// Text which can be identified by an [`Engine`], i.e. `str` or `[u8]`.
+///
+/// This trait is sealed, it can't be implemented outside of lemmeknow.
+pub trait Haystack: private::Sealed + ToOwned + Sync + 'static {
+    /// Compiled regex for this kind of text
+    type Regex: Sync + 'static;
+    /// Identification owning its text, see [`MatchRef::into_owned`]
+    type Match: Serialize + Send;
+
+    /// Regexes of the built-in patterns for the given mode, in the order of the patterns
+    fn builtin(mode: MatchMode) -> &'static [Lazy<Self::Regex>];
+    /// Regex of an added or overridden pattern for the given mode
+    fn pattern_regex(pattern: &Pattern, mode: MatchMode) -> &Self::Regex;
+    fn is_match(regex: &Self::Regex, text: &Self) -> bool;
+    /// Length of the leftmost match of `regex` in `text`, in bytes
+    fn find_len(regex: &Self::Regex, text: &Self) -> Option<usize>;
+    /// Length of the text in bytes
+    fn byte_len(&self) -> usize;
+    /// Copy a borrowed match, see [`MatchRef::into_owned`]
+    fn into_match(result: MatchRef<'_, Self>) -> Self::Match;
+    /// Text and data of an owned match
+    fn match_parts(result: &Self::Match) -> (&Self, &Data);
+    /// Beginning of the text, at most `len` bytes long
+    fn truncated(&self, len: usize) -> &Self;
+    /// Part of the text in the range of bytes, which is the `span` of a [`Normalized`] text
+    fn slice(&self, range: Range<usize>) -> &Self;
+    /// Whether the byte is a part of the printable strings read from binary files, must be `false` for `\n`
+    fn is_string_byte(byte: u8) -> bool;
+    /// Text of a string read from a binary file, i.e. bytes for which [`Haystack::is_string_byte`] is `true`
+    fn from_string_bytes(bytes: &[u8]) -> &Self;
+    /// Text as a filename, see `file_support`
+    fn file_name(&self) -> Option<&str>;
+    /// Normalized text, which is refanged too if `refang` is `true`,
+    /// see [`normalize`](crate::normalize) and [`defang`](crate::defang)
+    fn normalized(&self, normalize: &Normalize, refang: bool) -> Normalized<'_, Self>;
+    /// Texts in a string read from a text file, along with their offset in it
+    fn texts(string: &[u8]) -> impl Iterator<Item = (usize, &Self)>;
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for str {}
+    impl Sealed for [u8] {}
+}
+
+/// Configuration for identifying text, generic over the kind of text.
+///
+/// Use it through [`Identifier`](super::Identifier) for `str`
+/// or [`bytes::Identifier`](super::bytes::Identifier) for `[u8]`,
+/// both have every builder method and filter.
+///
+/// This can be deserialized, missing fields take their value from [`Engine::default`]
+/// and tags are read from `include` and `exclude`:
+///
+/// ```
+/// let identifier: lemmeknow::Identifier = serde_json::from_str(r#"{"min_rarity": 0.5, "exclude": ["Identifiers"]}"#).unwrap();
+/// assert_eq!(identifier.exclude_tags, ["Identifiers"]);
+/// ```
+#[derive(Deserialize)]
+#[serde(default, bound = "")]
+pub struct Engine<H: ?Sized> {
+    /// Keep Data having minimum Rarity of supplied `min_rarity`
+    pub min_rarity: f32,
+    /// Keep Data having maximum Rarity of supplied `max_rarity`
+    pub max_rarity: f32,
+    /// Only include the Data which have the specified `tags`, see `tag_match`
+    #[serde(rename = "include")]
+    pub tags: Vec<String>,
+    /// Only include Data which doesn't have any of the `excluded_tags`
+    #[serde(rename = "exclude")]
+    pub exclude_tags: Vec<String>,
+    /// Only include the Data having one of these IDs or a name matching one of the `names`
+    #[serde(rename = "include_ids")]
+    pub ids: Vec<String>,
+    /// Only include the Data whose name matches one of these globs, or having one of the `ids`
+    #[serde(rename = "include_names")]
+    pub names: Vec<String>,
+    /// Exclude the Data having any of these IDs
+    pub exclude_ids: Vec<String>,
+    /// Exclude the Data whose name matches any of these globs
+    pub exclude_names: Vec<String>,
+    /// Whether Data needs any or all of the `tags`
+    pub tag_match: TagMatch,
+    /// Only include the Data whose tags satisfy this expression
+    pub tag_expr: Option<TagExpr>,
+    /// How patterns have to match the text,
+    /// read from `boundaryless` too, where `true` is boundaryless and `false` is anchored
+    #[serde(alias = "boundaryless")]
+    pub mode: MatchMode,
+    /// Scan files having supplied text as filename
+    pub file_support: bool,
+    /// Refang defanged text like `hxxps://evil[.]com` before matching, see [`defang`](crate::defang)
+    pub refang: bool,
+    /// Steps which normalize text before matching, see [`normalize`](crate::normalize)
+    pub normalize: Normalize,
+    /// Where files and diffs are scanned, read from number of threads in `jobs`
+    #[serde(rename = "jobs")]
+    pub parallelism: Parallelism,
+    /// Stop scanning once this many matches are found, see [`limits`](crate::limits)
+    pub max_matches: Option<usize>,
+    /// Drop the matches of a pattern once it has this many
+    pub max_matches_per_pattern: Option<usize>,
+    /// Only scan this many bytes from the beginning of every text, file or diff
+    pub max_input_size: Option<usize>,
+    /// Stop scanning once this much time has passed since the scan started, read from seconds
+    #[serde(deserialize_with = "crate::limits::deserialize_secs")]
+    pub deadline: Option<Duration>,
+    /// How binary files are scanned, see [`sniff`](crate::sniff)
+    pub binary: BinaryPolicy,
+    /// Skip files larger than this many bytes
+    pub max_filesize: Option<u64>,
+    /// Patterns to use along with the built-in ones, see [`pattern`](crate::pattern)
+    #[serde(skip)]
+    pub patterns: Vec<Pattern>,
+    /// Built-in patterns changed by [`Engine::override_pattern`] by their ID, `None` if disabled
+    #[serde(deserialize_with = "deserialize_overrides")]
+    pub(crate) overrides: HashMap<&'static str, Option<Pattern>>,
+    #[serde(skip)]
+    haystack: PhantomData<fn(&H)>,
+}
+
+impl<H: ?Sized> Engine<H> {
+    #[inline]
+    pub fn min_rarity(mut self, rarity: f32) -> Self {
+        self.min_rarity = rarity;
+        self
+    }
+
+    #[inline]
+    pub fn max_rarity(mut self, rarity: f32) -> Self {
+        self.max_rarity = rarity;
+        self
+    }
+
+    #[inline]
+    pub fn include_tags(mut self, tags: &[String]) -> Self {
+        self.tags.extend_from_slice(tags);
+        self
+    }
+
+    #[inline]
+    pub fn exclude_tags(mut self, tags: &[String]) -> Self {
+        self.exclude_tags.extend_from_slice(tags);
+        self
+    }
+
+    #[inline]
+    pub fn include_ids(mut self, ids: &[String]) -> Self {
+        self.ids.extend_from_slice(ids);
+        self
+    }
+
+    #[inline]
+    pub fn exclude_ids(mut self, ids: &[String]) -> Self {
+        self.exclude_ids.extend_from_slice(ids);
+        self
+    }
+
+    /// Names can contain `*` to match any text and `?` to match any character.
+    #[inline]
+    pub fn include_names(mut self, names: &[String]) -> Self {
+        self.names.extend_from_slice(names);
+        self
+    }
+
+    /// Names can contain `*` to match any text and `?` to match any character.
+    #[inline]
+    pub fn exclude_names(mut self, names: &[String]) -> Self {
+        self.exclude_names.extend_from_slice(names);
+        self
+    }
+
+    #[inline]
+    pub fn tag_match(mut self, tag_match: TagMatch) -> Self {
+        self.tag_match = tag_match;
+        self
+    }
+
+    #[inline]
+    pub fn tag_expr(mut self, expr: TagExpr) -> Self {
+        self.tag_expr = Some(expr);
+        self
+    }
+
+    #[inline]
+    pub fn mode(mut self, mode: MatchMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Shorthand for [`MatchMode::Boundaryless`] if `true`, [`MatchMode::Anchored`] otherwise.
+    #[inline]
+    pub fn boundaryless(mut self, boundaryless: bool) -> Self {
+        self.mode = if boundaryless {
+            MatchMode::Boundaryless
+        } else {
+            MatchMode::Anchored
+        };
+        self
+    }
+
+    #[inline]
+    pub fn file_support(mut self, support: bool) -> Self {
+        self.file_support = support;
+        self
+    }
+
+    #[inline]
+    pub fn refang(mut self, refang: bool) -> Self {
+        self.refang = refang;
+        self
+    }
+
+    #[inline]
+    pub fn normalize(mut self, normalize: Normalize) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    /// Scan with a new pool of `n` threads, see [`Parallelism::threads`].
+    ///
+    /// Returns an error if the thread pool can't be created.
+    #[inline]
+    pub fn threads(mut self, n: usize) -> io::Result<Self> {
+        self.parallelism = Parallelism::threads(n)?;
+        Ok(self)
+    }
+
+    /// Scan with the given thread pool instead of rayon's global one.
+    #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+    #[inline]
+    pub fn with_pool(mut self, pool: Arc<ThreadPool>) -> Self {
+        self.parallelism = Parallelism::Pool(pool);
+        self
+    }
+
+    /// Scan everything on the calling thread.
+    #[inline]
+    pub fn sequential(mut self) -> Self {
+        self.parallelism = Parallelism::Sequential;
+        self
+    }
+
+    #[inline]
+    pub fn max_matches(mut self, max: usize) -> Self {
+        self.max_matches = Some(max);
+        self
+    }
+
+    #[inline]
+    pub fn max_matches_per_pattern(mut self, max: usize) -> Self {
+        self.max_matches_per_pattern = Some(max);
+        self
+    }
+
+    /// Size in bytes, larger texts and files are only scanned up to it.
+    #[inline]
+    pub fn max_input_size(mut self, size: usize) -> Self {
+        self.max_input_size = Some(size);
+        self
+    }
+
+    /// Time allowed for every scan, e.g. a call to [`Engine::identify_paths`].
+    #[inline]
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    #[inline]
+    pub fn binary(mut self, policy: BinaryPolicy) -> Self {
+        self.binary = policy;
+        self
+    }
+
+    /// Size in bytes, larger files are skipped.
+    #[inline]
+    pub fn max_filesize(mut self, size: u64) -> Self {
+        self.max_filesize = Some(size);
+        self
+    }
+
+    #[inline]
+    pub fn patterns(mut self, patterns: Vec<Pattern>) -> Self {
+        self.patterns.extend(patterns);
+        self
+    }
+
+    /// Change or disable the built-in pattern having `pattern` as its ID or name.
+    ///
+    /// Filters are applied to the changed pattern, e.g. tags added here can be used with `include_tags`.
+    ///
+    /// Returns an error with [`io::ErrorKind::InvalidInput`] if there is no such pattern,
+    /// or [`io::ErrorKind::InvalidData`] if the regex fails to compile.
+    ///
+    /// Strings of the override live for rest of the program, see [`Override::apply`](crate::pattern::Override::apply).
+    ///
+    /// ```
+    /// use lemmeknow::{pattern::Override, Identifier};
+    /// let with = Override { rarity: Some(0.1), ..Default::default() };
+    /// let identifier = Identifier::default().override_pattern("phone-number", with).unwrap();
+    ///
+    /// let result = identifier.identify("+91 9999999999");
+    /// assert_eq!(result[0].data.rarity, 0.1);
+    /// ```
+    pub fn override_pattern(mut self, pattern: &str, with: Override) -> io::Result<Self> {
+        let data = crate::pattern_by_id(pattern)
+            .or_else(|| crate::pattern_by_name(pattern))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("pattern `{pattern}` doesn't exist"),
+                )
+            })?;
+
+        let patched = if with.disabled {
+            None
+        } else {
+            Some(with.apply(data)?)
+        };
+        self.overrides.insert(data.id, patched);
+        Ok(self)
+    }
+}
+
+impl<H: ?Sized> Default for Engine<H> {
+    fn default() -> Self {
+        Engine {
+            min_rarity: 0.0,
+            max_rarity: 1.0,
+            tags: vec![],
+            exclude_tags: vec![],
+            ids: vec![],
+            names: vec![],
+            exclude_ids: vec![],
+            exclude_names: vec![],
+            tag_match: TagMatch::Any,
+            tag_expr: None,
+            mode: MatchMode::Anchored,
+            file_support: false,
+            refang: false,
+            normalize: Normalize::default(),
+            parallelism: Parallelism::Global,
+            max_matches: None,
+            max_matches_per_pattern: None,
+            max_input_size: None,
+            deadline: None,
+            binary: BinaryPolicy::Strings,
+            max_filesize: None,
+            patterns: vec![],
+            overrides: HashMap::new(),
+            haystack: PhantomData,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<H: Haystack + ?Sized> Engine<H> {
+    /// Identify the given text.
+    ///
+    /// If `file_support` is `true` and text is the name of an existing file, the file is identified instead,
+    /// see [`Engine::identify_file`].
+    ///
+    /// Finds all possible identifications.
+    ///
+    /// # Arguments
+    ///
+    /// * text: &H - text which we want to identify, `&str` or `&[u8]`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let identifier = lemmeknow::Identifier::default();
+    /// let result = identifier.identify("UC11L3JDgDQMyH8iolKkVZ4w");
+    /// assert_eq!(result[0].data.name, "YouTube Channel ID");
+    ///
+    /// let identifier = lemmeknow::bytes::Identifier::default();
+    /// let result = identifier.identify(b"UC11L3JDgDQMyH8iolKkVZ4w");
+    /// assert_eq!(result[0].data.name, "YouTube Channel ID");
+    /// ```
+    ///
+    pub fn identify(&self, text: &H) -> Vec<H::Match> {
+        self.identify_with_stats(text).0
+    }
+
+    /// Same as [`Engine::identify`], along with [`ScanStats`] of the scan,
+    /// whose `truncated` tells whether a limit stopped it early, see [`limits`](crate::limits).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let identifier = lemmeknow::Identifier::default().max_input_size(4);
+    /// let (result, stats) = identifier.identify_with_stats("UC11L3JDgDQMyH8iolKkVZ4w");
+    /// assert!(result.is_empty());
+    /// assert!(stats.truncated.is_some());
+    /// ```
+    ///
+    pub fn identify_with_stats(&self, text: &H) -> (Vec<H::Match>, ScanStats) {
+        let budget = Budget::new(self);
+        if self.file_support {
+            if let Some(name) = text.file_name().filter(|name| scan::is_file(name)) {
+                let result = self
+                    .scan_file(Path::new(name), &budget, |_| {})
+                    .expect("File not found");
+                return (result, budget.finish());
+            }
+        }
+
+        let text = budget.input(text);
+        // iter has almost same or sometimes better performance than par_iter for single text!
+        let result = self.identify_within(text, Location::default(), &budget);
+        budget.record(0, text.byte_len(), 0, data_of::<H>(&result), |_| {});
+        (result, budget.finish())
+    }
+
+    /// Same as [`Engine::identify`], with results sorted from the best to the worst.
+    ///
+    /// See [`rank`](crate::rank) for how they are ranked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let identifier = lemmeknow::Identifier::default();
+    /// let result = identifier.identify_sorted("hello@example.com");
+    /// assert!(result.windows(2).all(|w| w[0].data.rarity >= w[1].data.rarity));
+    /// ```
+    ///
+    pub fn identify_sorted(&self, text: &H) -> Vec<H::Match> {
+        let mut result = self.identify(text);
+        self.sort(&mut result);
+        result
+    }
+
+    /// Best `n` identifications of the text, see [`Engine::identify_sorted`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let identifier = lemmeknow::Identifier::default();
+    /// let result = identifier.top_k("hello@example.com", 1);
+    /// assert_eq!(result.len(), 1);
+    /// assert_eq!(result[0].data.name, identifier.first_match("hello@example.com").unwrap().data.name);
+    /// ```
+    ///
+    pub fn top_k(&self, text: &H, n: usize) -> Vec<H::Match> {
+        let mut result = self.identify_sorted(text);
+        result.truncate(n);
+        result
+    }
+
+    /// Identify strings from the given file.
+    ///
+    /// Strings of text files are sequences of at least 4 bytes which are neither ascii whitespace
+    /// nor control characters. [`Identifier`](super::Identifier) splits them at invalid UTF-8,
+    /// while [`bytes::Identifier`](super::bytes::Identifier) matches them as they are.
+    ///
+    /// Binary files are scanned according to `binary`, see [`sniff`](crate::sniff).
+    /// By default only their printable strings are scanned: sequences of at least 4 ascii graphic characters
+    /// for [`Identifier`](super::Identifier), and the same as for text files
+    /// for [`bytes::Identifier`](super::bytes::Identifier), which keeps non-ascii bytes.
+    /// Binary files skipped by `binary` and files larger than `max_filesize` have no matches,
+    /// use [`Engine::identify_paths`] to know why a file was skipped.
+    ///
+    /// Regular files are mapped into memory instead of being copied, others like pipes are read into a buffer.
+    /// Unlike [`Engine::identify`], this doesn't depend on `file_support`
+    /// and returns an error if the file can't be read.
+    ///
+    /// Every match has `path`, `line` and `offset` set.
+    ///
+    /// # Arguments
+    ///
+    /// * path: impl AsRef<Path> - path of the file which we want to scan
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let identifier = lemmeknow::Identifier::default();
+    /// let result = identifier.identify_file("this/file/does/not/exist");
+    /// assert!(result.is_err());
+    /// ```
+    ///
+    pub fn identify_file(&self, path: impl AsRef<Path>) -> io::Result<Vec<H::Match>> {
+        self.scan_file(path.as_ref(), &Budget::new(self), |_| {})
+    }
+
+    /// Identify strings read from `reader` until its end, see [`Engine::identify_file`].
+    ///
+    /// Strings are normalized, refanged and joined the same way as for files,
+    /// except that binary content is never skipped. Every match has `line` and `offset` set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let identifier = lemmeknow::bytes::Identifier::default();
+    /// let reader = std::io::Cursor::new(b"\x00\xffhi\n8888888888\x00".to_vec());
+    /// let result = identifier.identify_reader(reader).unwrap();
+    /// assert_eq!(result[0].text, b"8888888888");
+    /// assert_eq!(result[0].offset, Some(5));
+    /// ```
+    ///
+    pub fn identify_reader(&self, mut reader: impl Read) -> io::Result<Vec<H::Match>> {
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content)?;
+
+        let budget = Budget::new(self);
+        let extract = self.extract(sniff(&content));
+        let (result, _) = self.scan_buffer(
+            budget.input(&content),
+            extract,
+            self.normalize.join_lines,
+            |text, line, offset| {
+                let location = Location {
+                    path: None,
+                    line: Some(line),
+                    offset: Some(offset),
+                };
+                self.identify_within(text, location, &budget)
+            },
+        );
+        Ok(result)
+    }
+
+    /// Identify strings from the content of a file, without copying them.
+    ///
+    /// Strings are read the same way as [`Engine::identify_file`], except that binary content
+    /// is never skipped, every match has `line` and `offset` of the string set, but not `path`.
+    /// Strings aren't normalized, refanged or joined, as matches borrow their text from `content`,
+    /// use [`Engine::identify_reader`] for that.
+    ///
+    /// # Arguments
+    ///
+    /// * content: &[u8] - content of the file which we want to scan
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let identifier = lemmeknow::Identifier::default();
+    /// let result = identifier.matches_in_buffer(b"\x00\x01hi\n8888888888\xff");
+    /// assert_eq!(result[0].text, "8888888888");
+    /// assert_eq!(result[0].line, Some(2));
+    ///
+    /// let identifier = lemmeknow::bytes::Identifier::default();
+    /// let result = identifier.matches_in_buffer(b"hi\n\x008888888888\n");
+    /// assert_eq!(result[0].text, b"8888888888");
+    /// assert_eq!((result[0].line, result[0].offset), (Some(2), Some(4)));
+    /// ```
+    ///
+    pub fn matches_in_buffer<'a>(&'a self, content: &'a [u8]) -> Vec<MatchRef<'a, H>> {
+        let budget = Budget::new(self);
+        let extract = self.extract(sniff(content));
+        let (result, _) = self.scan_buffer(
+            budget.input(content),
+            extract,
+            false,
+            |text, line, offset| {
+                self.matches_within(text, &budget)
+                    .into_iter()
+                    .map(|m| m.with_line(line).with_offset(offset))
+                    .collect()
+            },
+        );
+        result
+    }
+
+    /// Identify every file in `paths` in parallel, walking directories recursively.
+    ///
+    /// Files are scanned the same way as [`Engine::identify_file`], results are in the order of the paths
+    /// and files of a directory are sorted by their name.
+    /// Only regular files are scanned from directories, symbolic links to directories are not followed.
+    ///
+    /// Returns the matches along with [`ScanStats`] of the scan, or an error along with the path
+    /// if one of `paths` couldn't be read. Files and directories found while walking which can't be read,
+    /// e.g. dangling symbolic links, are listed in `skipped` instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let identifier = lemmeknow::Identifier::default();
+    /// let (result, stats) = identifier.identify_paths(&["src/data"]).unwrap();
+    /// assert!(stats.files > 0);
+    /// assert_eq!(stats.matches, result.len());
+    /// ```
+    ///
+    pub fn identify_paths<P: AsRef<Path>>(
+        &self,
+        paths: &[P],
+    ) -> io::Result<(Vec<H::Match>, ScanStats)> {
+        self.identify_paths_with(paths, |_| false, |_, _| {})
+    }
+
+    /// Same as [`Engine::identify_paths`], skipping the files and directories for which `skip` returns `true`.
+    ///
+    /// `progress` is called after every scanned file with the counters so far and the number of files to scan,
+    /// e.g. for showing a progress bar. It is called from multiple threads, but never at the same time.
+    /// It is also called for files skipped by `binary`, `max_filesize` or as unreadable, which are listed in `skipped`,
+    /// but not for files left once a limit stops the scan.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let identifier = lemmeknow::Identifier::default();
+    /// let skip = |path: &std::path::Path| path.ends_with("data");
+    /// let (result, stats) = identifier
+    ///     .identify_paths_with(&["src"], skip, |stats, total| assert!(stats.files <= total))
+    ///     .unwrap();
+    /// assert!(result.iter().all(|m| !m.path.as_ref().unwrap().contains("data/")));
+    /// ```
+    ///
+    pub fn identify_paths_with<P: AsRef<Path>>(
+        &self,
+        paths: &[P],
+        skip: impl Fn(&Path) -> bool,
+        progress: impl Fn(&ScanStats, usize) + Sync,
+    ) -> io::Result<(Vec<H::Match>, ScanStats)> {
+        let budget = Budget::new(self);
+        let mut files = Vec::new();
+        let mut unreadable = Vec::new();
+        let paths: Vec<&Path> = paths.iter().map(AsRef::as_ref).collect();
+        for path in &paths {
+            scan::walk(path, &skip, true, &mut files, &mut unreadable)?;
+        }
+        for (path, e) in unreadable {
+            let reason = SkipReason::Unreadable {
+                error: e.to_string(),
+            };
+            budget.skip(&path.to_string_lossy(), reason, |_| {});
+        }
+
+        let result = self.parallelism.map(&files, |path| {
+            if budget.exhausted() {
+                return Ok(Vec::new());
+            }
+            let explicit = paths.contains(&path.as_path());
+            match self.scan_file(path, &budget, |stats| progress(stats, files.len())) {
+                Err(e) if !explicit => {
+                    let reason = SkipReason::Unreadable {
+                        error: e.to_string(),
+                    };
+                    budget.skip(&path.to_string_lossy(), reason, |stats| {
+                        progress(stats, files.len())
+                    });
+                    Ok(Vec::new())
+                }
+                result => result.map_err(|e| scan::with_path(e, path)),
+            }
+        });
+        let result = result.into_iter().collect::<io::Result<Vec<_>>>()?;
+
+        Ok((result.into_iter().flatten().collect(), budget.finish()))
+    }
+
+    // matches of the file, counted in `budget`
+    fn scan_file(
+        &self,
+        path: &Path,
+        budget: &Budget,
+        progress: impl FnOnce(&ScanStats),
+    ) -> io::Result<Vec<H::Match>> {
+        let filename = path.to_string_lossy();
+        let content = match scan::read(path, self.max_filesize)? {
+            Ok(content) => content,
+            Err(size) => {
+                let max_filesize = self.max_filesize.unwrap_or_default();
+                let reason = SkipReason::TooLarge { size, max_filesize };
+                budget.skip(&filename, reason, progress);
+                return Ok(Vec::new());
+            }
+        };
+        let binary = sniff(&content);
+        if let (Some(binary), BinaryPolicy::Skip) = (binary, self.binary) {
+            budget.skip(&filename, SkipReason::Binary { binary }, progress);
+            return Ok(Vec::new());
+        }
+
+        let content = budget.input(&*content);
+        let (found, strings) = self.scan_buffer(
+            content,
+            self.extract(binary),
+            self.normalize.join_lines,
+            |text, line, offset| {
+                let location = Location {
+                    path: Some(&filename),
+                    line: Some(line),
+                    offset: Some(offset),
+                };
+                self.identify_within(text, location, budget)
+            },
+        );
+        budget.record(1, content.len(), strings, data_of::<H>(&found), progress);
+        Ok(found)
+    }
+
+    // how strings are read from content, which is binary if `binary` is some
+    fn extract(&self, binary: Option<Binary>) -> Extract {
+        match binary {
+            Some(_) if self.binary != BinaryPolicy::Full => Extract::Strings,
+            _ => Extract::Text,
+        }
+    }
+
+    // results of `f` for every string in `content`, called with its line number and offset,
+    // along with the number of strings. Lines of text are joined if `join_lines` is `true`.
+    fn scan_buffer<'a, T: Send>(
+        &'a self,
+        content: &'a [u8],
+        extract: Extract,
+        join_lines: bool,
+        f: impl Fn(&'a H, usize, usize) -> Vec<T> + Sync + Send,
+    ) -> (Vec<T>, usize) {
+        match extract {
+            Extract::Strings => scan::strings(
+                &self.parallelism,
+                content,
+                H::is_string_byte,
+                false,
+                |line, offset, bytes| f(H::from_string_bytes(bytes), line, offset),
+            ),
+            Extract::Text => scan::strings(
+                &self.parallelism,
+                content,
+                scan::is_text_byte,
+                join_lines,
+                |line, offset, bytes| {
+                    H::texts(bytes)
+                        .flat_map(|(start, text)| f(text, line, offset + start))
+                        .collect()
+                },
+            ),
+        }
+    }
+
+    /// Matches of `text` found at `location`, within the limits of `budget`.
+    ///
+    /// Text is normalized first, and refanged if `refang` is `true`.
+    pub(super) fn identify_within(
+        &self,
+        text: &H,
+        location: Location<'_>,
+        budget: &Budget,
+    ) -> Vec<H::Match> {
+        let Normalized {
+            text: normalized,
+            span,
+        } = text.normalized(&self.normalize, self.refang);
+        let original = match normalized {
+            Cow::Owned(_) => Some(text.slice(span.clone())),
+            Cow::Borrowed(_) => None,
+        };
+
+        self.matches_within(&normalized, budget)
+            .into_iter()
+            .map(|mut m| {
+                m.original = original;
+                m.path = location.path;
+                m.line = location.line;
+                m.offset = location.offset.map(|offset| offset + span.start);
+                m.into_owned()
+            })
+            .collect()
+    }
+
+    /// Matches of `text` which are within the limits of `budget`
+    fn matches_within<'a>(&'a self, text: &'a H, budget: &Budget) -> Vec<MatchRef<'a, H>> {
+        self.current_patterns()
+            .take_while(|_| !budget.exhausted())
+            .filter(|(e, regex)| is_valid_filter(self, e) && H::is_match(regex, text))
+            .filter(|(e, _)| budget.take(e))
+            .map(|(e, _)| MatchRef::new(text, e))
+            .collect()
+    }
+}
+
+/// Where a text was found, see [`MatchRef`]
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct Location<'a> {
+    pub(super) path: Option<&'a str>,
+    pub(super) line: Option<usize>,
+    pub(super) offset: Option<usize>,
+}
+
+// Identifier implementation for wasm
+#[cfg(target_arch = "wasm32")]
+impl<H: Haystack + ?Sized> Engine<H> {
+    // There is no file system on the web, so we are not reading strings from file.
+    // let the user perform the I/O and read the file, then pass the content of it.
+    // Texts are normalized and refanged the same way as on other targets.
+    pub fn identify<T: AsRef<H>>(&self, text: &[T]) -> Vec<H::Match> {
+        text.iter()
+            .flat_map(|text| {
+                let text = text.as_ref();
+                let Normalized {
+                    text: normalized,
+                    span,
+                } = text.normalized(&self.normalize, self.refang);
+                let original = match normalized {
+                    Cow::Owned(_) => Some(text.slice(span)),
+                    Cow::Borrowed(_) => None,
+                };
+                self.matches(&normalized)
+                    .map(|m| match original {
+                        Some(original) => m.with_original(original).into_owned(),
+                        None => m.into_owned(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+impl<H: Haystack + ?Sized> Engine<H> {
+    /// This returns the best identification, see [`rank`](crate::rank) for how they are ranked.
+    ///
+    /// Same as the first result of [`Engine::identify_sorted`], without identifying files or applying limits.
+    ///
+    /// # Arguments
+    ///
+    /// * text: &H - text which we want to identify, `&str` or `&[u8]`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let identifier = lemmeknow::Identifier::default();
+    /// let some_result = identifier.first_match("8888888888");
+    /// let not_gonna_find = identifier.first_match("a friend for swanandx");
+    ///
+    /// assert_eq!(some_result.unwrap().data.name, "Phone Number");
+    /// assert!(not_gonna_find.is_none());
+    /// ```
+    ///
+    pub fn first_match(&self, text: &H) -> Option<H::Match> {
+        let Normalized {
+            text: normalized,
+            span,
+        } = text.normalized(&self.normalize, self.refang);
+        let haystack = &*normalized;
+
+        let mut best: Option<(Rank, &Data)> = None;
+        for (data, regex) in self.matching(haystack) {
+            let rank = rank_of::<H>(data, regex, haystack);
+            // earlier patterns win ties
+            if best.is_none_or(|(best, _)| rank > best) {
+                best = Some((rank, data));
+            }
+        }
+
+        best.map(|(_, data)| {
+            let result = MatchRef::new(haystack, data);
+            match normalized {
+                Cow::Owned(_) => result.with_original(text.slice(span)).into_owned(),
+                Cow::Borrowed(_) => result.into_owned(),
+            }
+        })
+    }
+
+    /// Lazily identify the given text, without copying it or the data of patterns.
+    ///
+    /// Unlike [`Engine::identify`], this doesn't read files, and the text is matched as it is,
+    /// i.e. it isn't normalized or refanged, as matches borrow it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let identifier = lemmeknow::Identifier::default();
+    /// let text = String::from("UC11L3JDgDQMyH8iolKkVZ4w");
+    /// let result: Vec<_> = identifier.matches(&text).collect();
+    /// assert_eq!(result[0].data.name, "YouTube Channel ID");
+    /// ```
+    ///
+    pub fn matches<'a>(&'a self, text: &'a H) -> impl Iterator<Item = MatchRef<'a, H>> + 'a {
+        self.matching(text)
+            .map(move |(e, _)| MatchRef::new(text, e))
+    }
+
+    /// Built-in and added patterns which pass the filters and match `text`, along with their regex
+    fn matching<'a>(&'a self, text: &'a H) -> impl Iterator<Item = (&'a Data, &'a H::Regex)> + 'a {
+        self.current_patterns()
+            .filter(move |(e, regex)| is_valid_filter(self, e) && H::is_match(regex, text))
+    }
+
+    /// Built-in and added patterns along with their regex for the current mode
+    fn current_patterns(&self) -> impl Iterator<Item = (&Data, &H::Regex)> {
+        // only the regexes of current mode are compiled
+        DATA.iter()
+            .zip(H::builtin(self.mode))
+            .filter_map(move |(e, regex)| match self.overrides.get(e.id) {
+                None => Some((e, &**regex)),
+                Some(patched) => patched
+                    .as_ref()
+                    .map(|p| (&p.data, H::pattern_regex(p, self.mode))),
+            })
+            .chain(
+                self.patterns
+                    .iter()
+                    .map(|p| (&p.data, H::pattern_regex(p, self.mode))),
+            )
+    }
+}
+
+// Ranking
+impl<H: Haystack + ?Sized> Engine<H> {
+    /// Rank of the match, as if it was found by this identifier.
+    pub fn rank(&self, result: &H::Match) -> Rank {
+        let (text, data) = H::match_parts(result);
+        let regex = self
+            .current_patterns()
+            .find(|(e, _)| e.id == data.id && e.regex == data.regex)
+            .map(|(_, regex)| regex);
+
+        match regex {
+            Some(regex) => rank_of::<H>(data, regex, text),
+            None => Rank::new(data, text.byte_len(), text.byte_len()),
+        }
+    }
+
+    /// Sort results from the best to the worst, see [`rank`](crate::rank).
+    ///
+    /// The sort is stable, i.e. results having same rank keep their order.
+    pub fn sort(&self, result: &mut [H::Match]) {
+        result.sort_by_cached_key(|m| Reverse(self.rank(m)));
+    }
+}
+
+// Output Implementation
+impl<H: Haystack + ?Sized> Engine<H> {
+    /// Convert [`Vec<Match>`](super::Match) to JSON
+    ///
+    /// Returns prettified JSON string.
+    ///
+    /// Helpful if you want to convert possible identifications to JSON
+    /// for using in web APIs or something else.
+    ///
+    /// # Arguments
+    ///
+    /// * result: `&[H::Match]` - Reference to `Vec<Match>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lemmeknow::Identifier;
+    /// let identifier = Identifier::default();
+    /// let result = identifier.identify("UC11L3JDgDQMyH8iolKkVZ4w");
+    /// let result_in_json = Identifier::to_json(&result);
+    /// println!("{result_in_json}");
+    /// ```
+    ///
+    #[inline]
+    pub fn to_json(result: &[H::Match]) -> String {
+        serde_json::to_string_pretty(result).unwrap_or_default()
+    }
+}
+
+fn rank_of<H: Haystack + ?Sized>(data: &Data, regex: &H::Regex, text: &H) -> Rank {
+    let span = H::find_len(regex, text).unwrap_or(text.byte_len());
+    Rank::new(data, span, text.byte_len())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn data_of<'a, H: Haystack + ?Sized + 'a>(
+    result: &'a [H::Match],
+) -> impl Iterator<Item = &'a Data> {
+    result.iter().map(|m| H::match_parts(m).1)
+}
+
+fn deserialize_overrides<'de, D>(
+    deserializer: D,
+) -> Result<HashMap<&'static str, Option<Pattern>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    HashMap::<String, Override>::deserialize(deserializer)?
+        .into_iter()
+        .try_fold(Engine::<str>::default(), |identifier, (pattern, with)| {
+            identifier.override_pattern(&pattern, with)
+        })
+        .map(|identifier| identifier.overrides)
+        .map_err(serde::de::Error::custom)
+}
+
+fn is_valid_filter<H: ?Sized>(configs: &Engine<H>, regex_data: &Data) -> bool {
+    if regex_data.rarity < configs.min_rarity {
+        return false;
+    }
+    if regex_data.rarity > configs.max_rarity {
+        return false;
+    }
+
+    let has_id = |ids: &[String]| ids.iter().any(|id| id.eq_ignore_ascii_case(regex_data.id));
+    let has_name = |names: &[String]| names.iter().any(|n| matches_glob(n, regex_data.name));
+    if (!configs.ids.is_empty() || !configs.names.is_empty())
+        && !has_id(&configs.ids)
+        && !has_name(&configs.names)
+    {
+        return false;
+    }
+    if has_id(&configs.exclude_ids) || has_name(&configs.exclude_names) {
+        return false;
+    }
+    if !configs.tag_match.matches(&configs.tags, regex_data.tags) {
+        return false;
+    }
+    if configs
+        .exclude_tags
+        .iter()
+        .any(|y| regex_data.tags.iter().any(|x| x.eq_ignore_ascii_case(y)))
+    {
+        return false;
+    }
+    if let Some(expr) = &configs.tag_expr {
+        return expr.matches(regex_data.tags);
+    }
+
+    true
+}
@@ -1,17 +1,28 @@
 //! For identifying text / analyzing files
 
 #[cfg(not(target_arch = "wasm32"))]
-use {
-    rayon::iter::{IntoParallelRefIterator, ParallelIterator},
-    std::{fs, str},
-};
+use crate::{limits::Budget, stats::ScanStats};
 
 pub mod bytes;
+mod engine;
+#[cfg(not(target_arch = "wasm32"))]
+mod scan;
+
+#[cfg(not(target_arch = "wasm32"))]
+use self::engine::Location;
+pub use self::engine::{Engine, Haystack};
+
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
 
 use once_cell::sync::Lazy;
 use regex::Regex;
-use serde::Serialize;
+use serde::{Deserialize, Deserializer, Serialize};
 
+use crate::normalize::{Normalize, Normalized};
+use crate::pattern::Pattern;
 use crate::Data;
 use crate::DATA;
 
@@ -20,310 +31,485 @@ include!(concat!(env!("OUT_DIR"), "/regex_data.rs"));
 
 /// structure containing the text and it's possible identification.
 #[derive(Serialize, Debug)]
+#[non_exhaustive]
 pub struct Match {
     pub text: String,
     pub data: Data,
+    /// Path of the file in which `text` was found, if it came from a file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Line number (starting from 1) at which `text` was found, if it came from a file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// Offset in bytes at which `text` was found, if it came from a file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    /// Text as it was written at `offset`, if it was normalized or refanged before matching,
+    /// see [`normalize`](crate::normalize) and [`defang`](crate::defang)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original: Option<String>,
 }
 
 impl Match {
     pub fn new(text: String, data: Data) -> Match {
-        Match { text, data }
+        Match {
+            text,
+            data,
+            path: None,
+            line: None,
+            offset: None,
+            original: None,
+        }
     }
-}
 
-pub struct Identifier {
-    /// Keep Data having minimum Rarity of supplied `min_rarity`
-    pub min_rarity: f32,
-    /// Keep Data having maximum Rarity of supplied `max_rarity`
-    pub max_rarity: f32,
-    /// Only include the Data which have at least one of the specified `tags`
-    pub tags: Vec<String>,
-    /// Only include Data which doesn't have any of the `excluded_tags`
-    pub exclude_tags: Vec<String>,
-    /// Use boundaryless regex
-    pub boundaryless: bool,
-    /// Scan files having supplied text as filename
-    pub file_support: bool,
-}
+    /// Set the path of the file in which `text` was found
+    #[inline]
+    pub fn with_path(mut self, path: &str) -> Match {
+        self.path = Some(path.to_owned());
+        self
+    }
 
-impl Identifier {
+    /// Set the line number at which `text` was found
     #[inline]
-    pub fn min_rarity(mut self, rarity: f32) -> Self {
-        self.min_rarity = rarity;
+    pub fn with_line(mut self, line: usize) -> Match {
+        self.line = Some(line);
         self
     }
 
+    /// Set the offset in bytes at which `text` was found
     #[inline]
-    pub fn max_rarity(mut self, rarity: f32) -> Self {
-        self.max_rarity = rarity;
+    pub fn with_offset(mut self, offset: usize) -> Match {
+        self.offset = Some(offset);
         self
     }
 
+    /// Set the text as it was written, before it was normalized or refanged
     #[inline]
-    pub fn include_tags(mut self, tags: &[String]) -> Self {
-        self.tags.extend_from_slice(tags);
+    pub fn with_original(mut self, original: &str) -> Match {
+        self.original = Some(original.to_owned());
         self
     }
+}
+
+/// Same as [`Match`], but borrowing the text and the data of the pattern instead of copying them.
+///
+/// `H` is the kind of text, `str` or `[u8]`, see [`Haystack`].
+/// Use [`MatchRef::into_owned`] to get a [`Match`] which can outlive the scanned text.
+#[derive(Serialize, Debug)]
+pub struct MatchRef<'a, H: ?Sized = str> {
+    pub text: &'a H,
+    pub data: &'a Data,
+    /// Path of the file in which `text` was found, if it came from a file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<&'a str>,
+    /// Line number (starting from 1) at which `text` was found, if it came from a file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// Offset in bytes at which `text` was found, if it came from a file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    /// Text as it was written at `offset`, if it was normalized or refanged before matching,
+    /// see [`normalize`](crate::normalize) and [`defang`](crate::defang)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original: Option<&'a H>,
+}
+
+// derive would require `H: Clone`, which `str` and `[u8]` aren't
+impl<H: ?Sized> Clone for MatchRef<'_, H> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
 
+impl<H: ?Sized> Copy for MatchRef<'_, H> {}
+
+impl<'a, H: ?Sized> MatchRef<'a, H> {
+    pub fn new(text: &'a H, data: &'a Data) -> MatchRef<'a, H> {
+        MatchRef {
+            text,
+            data,
+            path: None,
+            line: None,
+            offset: None,
+            original: None,
+        }
+    }
+
+    /// Set the path of the file in which `text` was found
+    #[inline]
+    pub fn with_path(mut self, path: &'a str) -> MatchRef<'a, H> {
+        self.path = Some(path);
+        self
+    }
+
+    /// Set the line number at which `text` was found
     #[inline]
-    pub fn exclude_tags(mut self, tags: &[String]) -> Self {
-        self.exclude_tags.extend_from_slice(tags);
+    pub fn with_line(mut self, line: usize) -> MatchRef<'a, H> {
+        self.line = Some(line);
         self
     }
 
+    /// Set the offset in bytes at which `text` was found
     #[inline]
-    pub fn boundaryless(mut self, boundaryless: bool) -> Self {
-        self.boundaryless = boundaryless;
+    pub fn with_offset(mut self, offset: usize) -> MatchRef<'a, H> {
+        self.offset = Some(offset);
         self
     }
 
+    /// Set the text as it was written, before it was normalized or refanged
     #[inline]
-    pub fn file_support(mut self, support: bool) -> Self {
-        self.file_support = support;
+    pub fn with_original(mut self, original: &'a H) -> MatchRef<'a, H> {
+        self.original = Some(original);
         self
     }
 }
 
-impl Default for Identifier {
-    fn default() -> Self {
-        Identifier {
-            min_rarity: 0.0,
-            max_rarity: 1.0,
-            tags: vec![],
-            exclude_tags: vec![],
-            boundaryless: false,
-            file_support: false,
-        }
+impl<H: Haystack + ?Sized> MatchRef<'_, H> {
+    /// Copy the text and data into a [`Match`], or a [`bytes::Match`] for `[u8]`
+    #[inline]
+    pub fn into_owned(self) -> H::Match {
+        H::into_match(self)
     }
 }
 
-// Identifier implementation
-#[cfg(not(target_arch = "wasm32"))]
-impl Identifier {
-    /// Identify the given text.
-    ///
-    /// This will read strings from file with text as filename if `file_support` is `true` and the file exists
-    ///
-    /// Finds all possible identifications.
-    ///
-    /// # Arguments
-    ///
-    /// * text: &str - text which we want to identify
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// let identifier = lemmeknow::Identifier::default();
-    /// let result = identifier.identify("UC11L3JDgDQMyH8iolKkVZ4w");
-    /// assert_eq!(result[0].data.name, "YouTube Channel ID");
-    /// ```
-    ///
-    pub fn identify(&self, text: &str) -> Vec<Match> {
-        let regexes = if self.boundaryless {
-            &BOUNDARYLESS_REGEX
-        } else {
-            &REGEX
-        };
+impl From<MatchRef<'_>> for Match {
+    #[inline]
+    fn from(result: MatchRef<'_>) -> Self {
+        result.into_owned()
+    }
+}
 
-        if self.file_support && is_file(text) {
-            let strings = read_file_to_strings(text);
-
-            strings
-                .par_iter()
-                .map(|text| {
-                    DATA.iter()
-                        .enumerate()
-                        .filter_map(|(i, e)| {
-                            if is_valid_filter(self, e) && regexes[i].is_match(text) {
-                                Some(Match::new(text.to_owned(), e.clone()))
-                            } else {
-                                None
-                            }
-                        })
-                        .collect::<Vec<Match>>()
-                })
-                .flatten()
-                .collect()
-        } else {
-            // iter has almost same or sometimes better performance than par_iter for single text!
-            DATA.iter()
-                .enumerate()
-                .filter_map(|(i, e)| {
-                    if is_valid_filter(self, e) && regexes[i].is_match(text) {
-                        Some(Match::new(text.to_owned(), e.clone()))
-                    } else {
-                        None
-                    }
-                })
-                .collect::<Vec<Match>>()
+/// How a pattern has to match the text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MatchMode {
+    /// Whole text has to match, e.g. `8888888888` but not `call 8888888888`
+    #[default]
+    Anchored,
+    /// Match has to be delimited by start or end of the text, whitespace,
+    /// quotes, `=`, `:`, `,`, `;` or brackets, e.g. `call 8888888888` but not `0x8888888888aa`
+    Word,
+    /// Match can be anywhere, even inside other text
+    Boundaryless,
+}
+
+impl MatchMode {
+    /// All modes, from the strictest to the loosest
+    pub const ALL: [MatchMode; 3] = [
+        MatchMode::Anchored,
+        MatchMode::Word,
+        MatchMode::Boundaryless,
+    ];
+
+    /// Name of the mode, as accepted by [`MatchMode::from_str`]
+    pub fn name(self) -> &'static str {
+        match self {
+            MatchMode::Anchored => "anchored",
+            MatchMode::Word => "word",
+            MatchMode::Boundaryless => "boundaryless",
         }
     }
+}
 
-    /// This returns the first identification.
-    ///
-    /// Due to how data is stored, this means that the returned result has the highest `rarity`.
-    ///
-    /// # Arguments
-    ///
-    /// * text: &str - text which we want to identify
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// let identifier = lemmeknow::Identifier::default();
-    /// let some_result = identifier.first_match("8888888888");
-    /// let not_gonna_find = identifier.first_match("a friend for swanandx");
-    ///  
-    /// assert_eq!(some_result.unwrap().data.name, "Phone Number");
-    /// assert!(not_gonna_find.is_none());
-    /// ```
-    ///
-    pub fn first_match(&self, text: &str) -> Option<Match> {
-        let regexes = if self.boundaryless {
-            &BOUNDARYLESS_REGEX
-        } else {
-            &REGEX
-        };
+impl fmt::Display for MatchMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
 
-        for (i, x) in DATA
-            .iter()
-            .enumerate()
-            .filter(|(_, x)| is_valid_filter(self, x))
-        {
-            // only consider the regex which compiles!
-            if regexes[i].is_match(text) {
-                return Some(Match::new(text.to_owned(), x.clone()));
-            }
+impl FromStr for MatchMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        MatchMode::ALL
+            .into_iter()
+            .find(|m| m.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("expected `anchored`, `word` or `boundaryless`, found `{s}`"))
+    }
+}
+
+// either name of the mode, or a bool for `boundaryless`
+impl<'de> Deserialize<'de> for MatchMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Boundaryless(bool),
+            Name(String),
         }
 
-        None
+        match Repr::deserialize(deserializer)? {
+            Repr::Boundaryless(true) => Ok(MatchMode::Boundaryless),
+            Repr::Boundaryless(false) => Ok(MatchMode::Anchored),
+            Repr::Name(name) => name.parse().map_err(serde::de::Error::custom),
+        }
     }
 }
 
-// Identifier implementation for wasm
-#[cfg(target_arch = "wasm32")]
-impl Identifier {
-    // There is no file system on the web, so we are not reading strings from file.
-    // let the user perform the I/O and read the file, then pass the content of it.
-    pub fn identify(&self, text: &[String]) -> Vec<Match> {
-        let regexes = if self.boundaryless {
-            &BOUNDARYLESS_REGEX
-        } else {
-            &REGEX
-        };
+/// Identifier for text, see [`Engine`] for its configuration and methods.
+pub type Identifier = Engine<str>;
+
+impl Haystack for str {
+    type Regex = Regex;
+    type Match = Match;
 
-        text.iter()
-            .flat_map(|text| {
-                DATA.iter().enumerate().filter_map(|(i, e)| {
-                    if is_valid_filter(self, e) && regexes[i].is_match(text) {
-                        Some(Match::new(text.to_owned(), e.clone()))
-                    } else {
-                        None
-                    }
-                })
+    #[inline]
+    fn builtin(mode: MatchMode) -> &'static [Lazy<Regex>] {
+        match mode {
+            MatchMode::Anchored => &REGEX,
+            MatchMode::Word => &WORD_REGEX,
+            MatchMode::Boundaryless => &BOUNDARYLESS_REGEX,
+        }
+    }
+
+    #[inline]
+    fn pattern_regex(pattern: &Pattern, mode: MatchMode) -> &Regex {
+        pattern.regex(mode)
+    }
+
+    #[inline]
+    fn is_match(regex: &Regex, text: &str) -> bool {
+        regex.is_match(text)
+    }
+
+    #[inline]
+    fn find_len(regex: &Regex, text: &str) -> Option<usize> {
+        regex.find(text).map(|m| m.len())
+    }
+
+    #[inline]
+    fn byte_len(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn into_match(result: MatchRef<'_>) -> Match {
+        Match {
+            text: result.text.to_owned(),
+            data: result.data.clone(),
+            path: result.path.map(str::to_owned),
+            line: result.line,
+            offset: result.offset,
+            original: result.original.map(ToOwned::to_owned),
+        }
+    }
+
+    #[inline]
+    fn match_parts(result: &Match) -> (&str, &Data) {
+        (&result.text, &result.data)
+    }
+
+    #[inline]
+    fn truncated(&self, mut len: usize) -> &str {
+        while !self.is_char_boundary(len) {
+            len -= 1;
+        }
+        &self[..len]
+    }
+
+    #[inline]
+    fn slice(&self, range: Range<usize>) -> &str {
+        &self[range]
+    }
+
+    // Doesn't consider whitespace as a graphic!
+    #[inline]
+    fn is_string_byte(byte: u8) -> bool {
+        byte.is_ascii_graphic()
+    }
+
+    #[inline]
+    fn from_string_bytes(bytes: &[u8]) -> &str {
+        std::str::from_utf8(bytes).expect("ascii graphic characters are valid utf-8")
+    }
+
+    #[inline]
+    fn file_name(&self) -> Option<&str> {
+        Some(self)
+    }
+
+    #[inline]
+    fn normalized(&self, normalize: &Normalize, refang: bool) -> Normalized<'_, str> {
+        let mut normalized = normalize.apply(self);
+        if refang {
+            if let Cow::Owned(refanged) = crate::defang::refang(&normalized.text) {
+                normalized.text = Cow::Owned(refanged);
+            }
+        }
+        normalized
+    }
+
+    // valid parts, split at invalid UTF-8
+    fn texts(string: &[u8]) -> impl Iterator<Item = (usize, &str)> {
+        string
+            .utf8_chunks()
+            .scan(0, |offset, chunk| {
+                let start = *offset;
+                *offset += chunk.valid().len() + chunk.invalid().len();
+                Some((start, chunk.valid()))
             })
-            .collect()
+            .filter(|(_, text)| !text.is_empty())
     }
 }
 
-// Output Implementation
+// Identifier implementation
+#[cfg(not(target_arch = "wasm32"))]
 impl Identifier {
-    /// Convert [`Vec<Match>`] to JSON
+    /// Identify strings from the lines added by a unified diff.
     ///
-    /// Returns prettified JSON string.
+    /// Removed and context lines are not scanned, see [`diff`](crate::diff) for details.
+    /// Like [`Identifier::identify_file`], only human readable strings are considered.
     ///
-    /// Helpful if you want to convert possible identifications to JSON
-    /// for using in web APIs or something else.
+    /// Every match has `path` and `line` set to the location in the new version of the file.
     ///
     /// # Arguments
     ///
-    /// * result: &[Match] - Reference to `Vec<Match>`.
+    /// * diff: &str - unified diff, e.g. output of `git diff`
     ///
     /// # Examples
     ///
     /// ```
-    /// use lemmeknow::Identifier;
-    /// let identifier = Identifier::default();
-    /// let result = identifier.identify("UC11L3JDgDQMyH8iolKkVZ4w");
-    /// let result_in_json = Identifier::to_json(&result);
-    /// println!("{result_in_json}");
+    /// let identifier = lemmeknow::Identifier::default();
+    /// let result = identifier.identify_diff("+++ b/numbers\n@@ -0,0 +1 @@\n+8888888888\n");
+    /// assert_eq!(result[0].data.name, "Phone Number");
+    /// assert_eq!(result[0].line, Some(1));
     /// ```
     ///
-    #[inline]
-    pub fn to_json(result: &[Match]) -> String {
-        serde_json::to_string_pretty(result).unwrap_or_default()
+    pub fn identify_diff(&self, diff: &str) -> Vec<Match> {
+        self.identify_diff_with_stats(diff).0
     }
-}
 
-// helper functions
-// TODO: try #[inline]
-#[cfg(not(target_arch = "wasm32"))]
-fn is_file(name: &str) -> bool {
-    if let Ok(s) = fs::metadata(name) {
-        s.is_file()
-    } else {
-        false
+    /// Same as [`Identifier::identify_diff`], along with [`ScanStats`] of the scan,
+    /// see [`Engine::identify_with_stats`].
+    pub fn identify_diff_with_stats(&self, diff: &str) -> (Vec<Match>, ScanStats) {
+        let budget = Budget::new(self);
+        let diff = budget.input(diff);
+        let result = self
+            .parallelism
+            .map(&crate::diff::added_lines(diff), |added| {
+                // same as strings read from a file, anything not ascii graphic is a separator
+                added
+                    .content
+                    .split(|c: char| !c.is_ascii_graphic())
+                    .filter(|text| text.len() >= 4)
+                    .flat_map(|text| {
+                        let location = Location {
+                            path: Some(added.path),
+                            line: Some(added.line),
+                            offset: None,
+                        };
+                        self.identify_within(text, location, &budget)
+                    })
+                    .collect::<Vec<Match>>()
+            });
+        let result: Vec<Match> = result.into_iter().flatten().collect();
+
+        budget.record(0, diff.len(), 0, result.iter().map(|m| &m.data), |_| {});
+        (result, budget.finish())
     }
 }
 
-fn is_valid_filter(configs: &Identifier, regex_data: &Data) -> bool {
-    if regex_data.rarity < configs.min_rarity {
-        return false;
-    }
-    if regex_data.rarity > configs.max_rarity {
-        return false;
-    }
+/// An example which didn't behave as expected, found by [`Identifier::self_test`].
+#[derive(Serialize, Debug, Clone)]
+pub struct ExampleFailure {
+    /// Name of the pattern
+    pub name: &'static str,
+    pub example: &'static str,
+    /// `true` if the example is a valid one, i.e. it should have matched
+    pub valid: bool,
+    /// `true` if it failed with the boundaryless regex
+    pub boundaryless: bool,
+}
 
-    if configs
-        .tags
-        .iter()
-        .any(|y| !regex_data.tags.iter().any(|x| x == y))
-    {
-        return false;
-    }
-    if configs
-        .exclude_tags
-        .iter()
-        .any(|y| regex_data.tags.iter().any(|x| x == y))
-    {
-        return false;
+impl fmt::Display for ExampleFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = if self.boundaryless {
+            "boundaryless"
+        } else {
+            "boundary"
+        };
+        if self.valid {
+            write!(
+                f,
+                "{}: valid example `{}` didn't match in {mode} mode",
+                self.name, self.example
+            )
+        } else {
+            write!(
+                f,
+                "{}: invalid example `{}` matched in {mode} mode",
+                self.name, self.example
+            )
+        }
     }
-
-    true
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-fn read_file_to_strings(filename: &str) -> Vec<String> {
-    let file = fs::read(filename).expect("File not found");
-
-    let mut printable_text: Vec<String> = Vec::new();
-    let mut buffer: Vec<u8> = Vec::new();
-    let mut use_current_buffer = false;
-
-    //we only need the human readable strings from the file.
-    for character in file {
-        if character.is_ascii_graphic() {
-            // Doesn't consider whitespace as a graphic!
-            use_current_buffer = true;
-            buffer.push(character);
-        } else if use_current_buffer {
-            // If the char isn't ascii graphic, that means this is the end for our string which we are interested in
-            // string with length less than 4 most likely won't be of our use.
-            // If it has length more than 4, then push it to our `printable_text`
-            if buffer.len() >= 4 {
-                printable_text.push(
-                    String::from_utf8(buffer.clone()).expect("failed to convert u8 to string"),
-                );
+impl Identifier {
+    /// Check the built-in and added patterns against their examples.
+    ///
+    /// With boundary, valid examples must match and invalid ones must not.
+    /// Boundaryless regex is only checked against valid examples,
+    /// as it is expected to find valid text inside an invalid example.
+    ///
+    /// Filters and `boundaryless` mode of the identifier are not considered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let identifier = lemmeknow::Identifier::default();
+    /// assert!(identifier.self_test().is_empty());
+    /// ```
+    ///
+    pub fn self_test(&self) -> Vec<ExampleFailure> {
+        let mut failures = Vec::new();
+
+        for (data, regex, boundaryless) in self.all_patterns() {
+            let failure = |example, valid, boundaryless| ExampleFailure {
+                name: data.name,
+                example,
+                valid,
+                boundaryless,
+            };
+
+            for example in data.examples.valid {
+                if !regex.is_match(example) {
+                    failures.push(failure(example, true, false));
+                }
+                if !boundaryless.is_match(example) {
+                    failures.push(failure(example, true, true));
+                }
+            }
+            for example in data.examples.invalid {
+                if regex.is_match(example) {
+                    failures.push(failure(example, false, false));
+                }
             }
-
-            // Clear the buffer so that current contents of it won't affect the next string.
-            buffer.clear();
-            // We set this to false because we don't want to use buffer until we get a ascii graphic!
-            use_current_buffer = false;
         }
-    }
 
-    printable_text.push(String::from_utf8(buffer).expect("failed to convert u8 to string"));
+        failures
+    }
 
-    printable_text
+    /// Built-in and added patterns along with their regex and boundaryless regex
+    fn all_patterns(&self) -> impl Iterator<Item = (&Data, &Regex, &Regex)> {
+        DATA.iter()
+            .zip(REGEX.iter().zip(BOUNDARYLESS_REGEX.iter()))
+            .filter_map(
+                |(e, (regex, boundaryless))| match self.overrides.get(e.id) {
+                    None => Some((e, &**regex, &**boundaryless)),
+                    Some(patched) => patched.as_ref().map(|p| {
+                        (
+                            &p.data,
+                            p.regex(MatchMode::Anchored),
+                            p.regex(MatchMode::Boundaryless),
+                        )
+                    }),
+                },
+            )
+            .chain(self.patterns.iter().map(|p| {
+                (
+                    &p.data,
+                    p.regex(MatchMode::Anchored),
+                    p.regex(MatchMode::Boundaryless),
+                )
+            }))
+    }
 }
@@ -0,0 +1,295 @@
+//! Reading files and splitting their content into strings, in parallel chunks
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use memmap2::Mmap;
+
+use crate::parallel::Parallelism;
+
+/// Content is split into chunks of this size, which are scanned in parallel
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// Strings shorter than this most likely won't be of our use
+const MIN_LEN: usize = 4;
+
+/// Lines indented by more than this many spaces or tabs aren't joined, see `join_lines`
+const MAX_INDENT: usize = 64;
+
+/// How strings are read from content
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Extract {
+    /// Printable strings of a binary, see [`Haystack::is_string_byte`](super::Haystack::is_string_byte)
+    Strings,
+    /// Strings of text, see [`is_text_byte`]
+    Text,
+}
+
+/// Whether the byte is a part of the strings of text, i.e. neither ascii whitespace nor a control character
+pub(super) fn is_text_byte(byte: u8) -> bool {
+    byte != b' ' && !byte.is_ascii_control()
+}
+
+/// Content of a file, either mapped into memory or read into a buffer.
+pub(super) enum Content {
+    Mapped(Mmap),
+    Buffered(Vec<u8>),
+}
+
+impl Deref for Content {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Content::Mapped(map) => map,
+            Content::Buffered(buffer) => buffer,
+        }
+    }
+}
+
+/// Map a regular file into memory, so it is not copied.
+///
+/// Special files like pipes, devices or `/proc` entries, which can't be mapped
+/// or don't report their real size, are read into a buffer instead.
+///
+/// Files larger than `max_size` are neither mapped nor read, their size is returned as `Err` instead.
+/// Special files are read up to one byte past `max_size`, which is the size returned for them.
+pub(super) fn read(path: &Path, max_size: Option<u64>) -> io::Result<Result<Content, u64>> {
+    let mut file = File::open(path)?;
+    let metadata = file.metadata()?;
+
+    if metadata.is_file() && metadata.len() > 0 {
+        if max_size.is_some_and(|max| metadata.len() > max) {
+            return Ok(Err(metadata.len()));
+        }
+        // SAFETY: the map is only read. If another process truncates the file while it is scanned,
+        // reading the missing part is an error we can't recover from, same as for any other mmap user.
+        if let Ok(map) = unsafe { Mmap::map(&file) } {
+            return Ok(Ok(Content::Mapped(map)));
+        }
+    }
+
+    let mut buffer = Vec::new();
+    match max_size {
+        Some(max_size) => {
+            file.take(max_size.saturating_add(1))
+                .read_to_end(&mut buffer)?;
+            if buffer.len() as u64 > max_size {
+                return Ok(Err(buffer.len() as u64));
+            }
+        }
+        None => {
+            file.read_to_end(&mut buffer)?;
+        }
+    }
+    Ok(Ok(Content::Buffered(buffer)))
+}
+
+/// Call `scan` with the line number, offset and bytes of every run of at least 4 bytes
+/// for which `keep` is `true`, and collect the results in order along with the number of runs.
+///
+/// `keep` must be `false` for `\n`. If `join_lines` is `true`, runs separated by a single line break
+/// and the indentation of the next line are joined along with them,
+/// the line number is the one at which the joined run starts.
+/// Content is scanned in parallel chunks,
+/// a run crossing the end of a chunk is scanned along with the chunk in which it starts,
+/// i.e. chunks overlap by the part of the run following their end.
+pub(super) fn strings<'a, T, F>(
+    parallelism: &Parallelism,
+    content: &'a [u8],
+    keep: fn(u8) -> bool,
+    join_lines: bool,
+    scan: F,
+) -> (Vec<T>, usize)
+where
+    T: Send,
+    F: Fn(usize, usize, &'a [u8]) -> Vec<T> + Sync + Send,
+{
+    let starts: Vec<usize> = (0..content.len()).step_by(CHUNK_SIZE).collect();
+
+    // line number at the start of every chunk
+    let newlines = parallelism.map(&starts, |&start| {
+        chunk(content, start)
+            .iter()
+            .filter(|&&b| b == b'\n')
+            .count()
+    });
+    let lines = newlines.iter().scan(1, |line, newlines| {
+        let first = *line;
+        *line += newlines;
+        Some(first)
+    });
+    let chunks: Vec<(usize, usize)> = starts.into_iter().zip(lines).collect();
+
+    let count = AtomicUsize::new(0);
+    let result = parallelism.map(&chunks, |&(start, line)| {
+        let runs = runs(content, start, line, keep, join_lines);
+        count.fetch_add(runs.len(), Ordering::Relaxed);
+        parallelism
+            .map(&runs, |&(line, offset, bytes)| scan(line, offset, bytes))
+            .into_iter()
+            .flatten()
+            .collect::<Vec<T>>()
+    });
+
+    (result.into_iter().flatten().collect(), count.into_inner())
+}
+
+fn chunk(content: &[u8], start: usize) -> &[u8] {
+    &content[start..content.len().min(start + CHUNK_SIZE)]
+}
+
+// runs starting in the chunk at `start`, along with their line number and offset
+fn runs(
+    content: &[u8],
+    start: usize,
+    mut line: usize,
+    keep: fn(u8) -> bool,
+    join_lines: bool,
+) -> Vec<(usize, usize, &[u8])> {
+    let end = content.len().min(start + CHUNK_SIZE);
+    let newlines = |bytes: &[u8]| bytes.iter().filter(|&&b| b == b'\n').count();
+    let mut runs = Vec::new();
+    let mut i = start;
+
+    // the run at the start belongs to the previous chunk
+    if let Some(last) = last_before(content, start, keep, join_lines) {
+        let run_end = run_end(content, last, keep, join_lines);
+        if run_end > start {
+            line += newlines(&content[start..run_end.min(end)]);
+            i = run_end;
+        }
+    }
+
+    while i < end {
+        if !keep(content[i]) {
+            if content[i] == b'\n' {
+                line += 1;
+            }
+            i += 1;
+            continue;
+        }
+
+        let run_start = i;
+        // may go past the end of the chunk
+        i = run_end(content, i, keep, join_lines);
+        if i - run_start >= MIN_LEN {
+            runs.push((line, run_start, &content[run_start..i]));
+        }
+        line += newlines(&content[run_start..i]);
+    }
+
+    runs
+}
+
+// end of the run containing the kept byte at `i`
+fn run_end(content: &[u8], mut i: usize, keep: fn(u8) -> bool, join_lines: bool) -> usize {
+    while i < content.len() {
+        if keep(content[i]) {
+            i += 1;
+        } else if let Some(len) = join_lines.then(|| line_break(content, i, keep)).flatten() {
+            i += len;
+        } else {
+            break;
+        }
+    }
+    i
+}
+
+// length of the line break at `i` along with the indentation of the next line,
+// if the next line starts with a kept byte which continues the run
+fn line_break(content: &[u8], i: usize, keep: fn(u8) -> bool) -> Option<usize> {
+    let rest = &content[i..];
+    let next = rest
+        .strip_prefix(b"\r\n")
+        .or_else(|| rest.strip_prefix(b"\n"))?;
+    let indent = next
+        .iter()
+        .take(MAX_INDENT + 1)
+        .take_while(|&&b| b == b' ' || b == b'\t')
+        .count();
+    (indent <= MAX_INDENT && next.get(indent).is_some_and(|&b| keep(b)))
+        .then_some(rest.len() - next.len() + indent)
+}
+
+// last kept byte before `start` whose run may continue at `start`
+fn last_before(
+    content: &[u8],
+    start: usize,
+    keep: fn(u8) -> bool,
+    join_lines: bool,
+) -> Option<usize> {
+    let gap = if join_lines {
+        // line break along with the indentation of the next line
+        MAX_INDENT + 2
+    } else {
+        0
+    };
+    content[start.saturating_sub(gap + 1)..start]
+        .iter()
+        .rposition(|&b| keep(b))
+        .map(|last| start.saturating_sub(gap + 1) + last)
+}
+
+// TODO: try #[inline]
+pub(super) fn is_file(name: &str) -> bool {
+    if let Ok(s) = fs::metadata(name) {
+        s.is_file()
+    } else {
+        false
+    }
+}
+
+// files in `path`, walking directories recursively in the order of their names.
+// Only errors for the `explicit` path are returned, entries which can't be read are added to `unreadable`.
+pub(super) fn walk(
+    path: &Path,
+    skip: &impl Fn(&Path) -> bool,
+    explicit: bool,
+    files: &mut Vec<PathBuf>,
+    unreadable: &mut Vec<(PathBuf, io::Error)>,
+) -> io::Result<()> {
+    if skip(path) {
+        return Ok(());
+    }
+    match entries(path, explicit) {
+        Ok(Some(mut entries)) => {
+            entries.sort();
+            for entry in entries {
+                walk(&entry, skip, false, files, unreadable)?;
+            }
+        }
+        Ok(None) => files.push(path.to_owned()),
+        Err(e) if explicit => return Err(with_path(e, path)),
+        Err(e) => unreadable.push((path.to_owned(), e)),
+    }
+    Ok(())
+}
+
+// entries of the directory at `path`, `None` if it's a file to scan
+fn entries(path: &Path, explicit: bool) -> io::Result<Option<Vec<PathBuf>>> {
+    let metadata = fs::metadata(path)?;
+
+    if metadata.is_dir() {
+        // symbolic links to directories may form cycles
+        if !explicit && fs::symlink_metadata(path)?.is_symlink() {
+            return Ok(Some(Vec::new()));
+        }
+        let entries = fs::read_dir(path)?
+            .map(|e| e.map(|e| e.path()))
+            .collect::<io::Result<_>>()?;
+        Ok(Some(entries))
+    } else if explicit || metadata.is_file() {
+        Ok(None)
+    } else {
+        // special files like pipes may block forever, unless asked for
+        Ok(Some(Vec::new()))
+    }
+}
+
+pub(super) fn with_path(e: io::Error, path: &Path) -> io::Error {
+    io::Error::new(e.kind(), format!("{}: {e}", path.display()))
+}
@@ -9,7 +9,7 @@
  *
  * ```toml
  * [dependencies]
- * lemmeknow = { version = "0.7", default-features = false }
+ * lemmeknow = { version = "0.9", default-features = false }
  * ```
  *
  * OR by using github repository:
@@ -19,6 +19,8 @@
  * lemmeknow = { git = "https://github.com/swanandx/lemmeknow", default-features = false }
  * ```
  *
+ * Add `features = ["parallel"]` to keep scanning files with multiple threads, see [`parallel`].
+ *
  * # Example:
  *
  * Let us say we want to identify a text and then get the output as pretty JSON
@@ -31,6 +33,8 @@
  * println!("{result_in_json}");
  * ```
  *
+ * To enumerate the built-in patterns, e.g. for building a tag picker, see [`catalogue`].
+ *
  * If you want to work with bytes, i.e. `[u8]` use [`bytes::Identifier`]
  *
  * ```rust
@@ -43,13 +47,38 @@
  *
  * */
 
+mod anchors;
+pub mod baseline;
+pub mod catalogue;
+pub mod defang;
+pub mod diff;
 pub mod identifier;
+pub mod limits;
+pub mod normalize;
+pub mod parallel;
+pub mod pattern;
+pub mod profile;
+pub mod rank;
+pub mod sniff;
+pub mod stats;
+pub mod suppress;
+pub mod tags;
+pub use self::catalogue::{
+    all_tags, dropped_patterns, pattern_by_id, pattern_by_name, patterns, patterns_named,
+    patterns_with_tag,
+};
 pub use self::identifier::bytes;
 pub use self::identifier::Identifier;
 pub use self::identifier::Match;
+pub use self::identifier::MatchMode;
+pub use self::identifier::MatchRef;
 
 use serde::Serialize;
 
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(feature = "cli")]
+pub mod config;
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(feature = "cli")]
 pub mod output;
@@ -61,6 +90,8 @@ pub use self::output::PrintMode;
 /// structure for parsing data from JSON file.
 #[derive(Serialize, Debug, Clone)]
 pub struct Data {
+    /// Stable identifier, e.g. `github-pat`, which doesn't change along with the name
+    pub id: &'static str,
     pub name: &'static str,
     pub regex: &'static str,
     boundaryless: &'static str,
@@ -70,9 +101,27 @@ pub struct Data {
     pub rarity: f32,
     pub url: Option<&'static str>,
     pub tags: &'static [&'static str],
+    #[serde(skip)]
+    pub examples: Examples,
+}
+
+impl Data {
+    /// Regex used in boundaryless mode, either given as `BoundarylessRegex`
+    /// or made by removing `^` and `$` anchors from `regex`
+    #[inline]
+    pub fn boundaryless(&self) -> &'static str {
+        self.boundaryless
+    }
+}
+
+/// Strings which should and should not be identified by a pattern.
+#[derive(Serialize, Debug, Clone, Copy, Default)]
+pub struct Examples {
+    pub valid: &'static [&'static str],
+    pub invalid: &'static [&'static str],
 }
 
-// this is DATA
+// this is DATA and DROPPED
 include!(concat!(env!("OUT_DIR"), "/data.rs"));
 
 #[cfg(test)]
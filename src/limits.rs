@@ -0,0 +1,208 @@
+/*!
+ * For limiting how much work a scan does.
+ *
+ * An identifier can cap the number of matches in total and per pattern,
+ * the number of bytes scanned from every input, and the time a scan takes.
+ * When a limit is hit, the scan stops early and the results are marked as truncated
+ * in the [`ScanStats`] of the scan.
+ *
+ * When scanning in parallel, which matches are kept is not deterministic, only how many.
+ * Limits are not applied on wasm.
+ *
+ * # Example:
+ *
+ * ```rust
+ * use lemmeknow::{limits::Truncation, Identifier};
+ * let identifier = Identifier::default().boundaryless(true).max_matches(1);
+ *
+ * let (result, stats) = identifier.identify_with_stats("hello@example.com");
+ * assert_eq!(result.len(), 1);
+ * assert_eq!(stats.truncated, Some(Truncation::MaxMatches));
+ * ```
+ */
+
+use std::fmt;
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    crate::identifier::{Engine, Haystack},
+    crate::sniff::{SkipReason, Skipped},
+    crate::stats::ScanStats,
+    crate::Data,
+    once_cell::sync::OnceCell,
+    std::collections::HashMap,
+    std::sync::atomic::{AtomicUsize, Ordering},
+    std::sync::Mutex,
+    std::time::Instant,
+};
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Limit which stopped a scan early
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Truncation {
+    /// `max_matches` were found
+    MaxMatches,
+    /// `max_matches_per_pattern` were found for a pattern, its other matches were dropped
+    MaxMatchesPerPattern,
+    /// An input was larger than `max_input_size`, only its beginning was scanned
+    MaxInputSize,
+    /// `deadline` has passed
+    Deadline,
+}
+
+impl Truncation {
+    /// Name of the setting which was hit
+    pub fn name(self) -> &'static str {
+        match self {
+            Truncation::MaxMatches => "max_matches",
+            Truncation::MaxMatchesPerPattern => "max_matches_per_pattern",
+            Truncation::MaxInputSize => "max_input_size",
+            Truncation::Deadline => "deadline",
+        }
+    }
+}
+
+impl fmt::Display for Truncation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Limits and counters of a single scan, shared by the threads doing it
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) struct Budget {
+    max_matches: Option<usize>,
+    max_matches_per_pattern: Option<usize>,
+    max_input_size: Option<usize>,
+    deadline: Option<Instant>,
+    start: Instant,
+    matches: AtomicUsize,
+    per_pattern: Mutex<HashMap<&'static str, usize>>,
+    // first limit which was hit
+    truncated: OnceCell<Truncation>,
+    stats: Mutex<ScanStats>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Budget {
+    pub(crate) fn new<H: ?Sized>(identifier: &Engine<H>) -> Budget {
+        let start = Instant::now();
+        Budget {
+            max_matches: identifier.max_matches,
+            max_matches_per_pattern: identifier.max_matches_per_pattern,
+            max_input_size: identifier.max_input_size,
+            deadline: identifier.deadline.map(|d| start + d),
+            start,
+            matches: AtomicUsize::new(0),
+            per_pattern: Mutex::new(HashMap::new()),
+            truncated: OnceCell::new(),
+            stats: Mutex::new(ScanStats::default()),
+        }
+    }
+
+    /// Beginning of the input which shall be scanned
+    pub(crate) fn input<'a, H: Haystack + ?Sized>(&self, input: &'a H) -> &'a H {
+        match self.max_input_size {
+            Some(max) if input.byte_len() > max => {
+                self.truncate(Truncation::MaxInputSize);
+                input.truncated(max)
+            }
+            _ => input,
+        }
+    }
+
+    /// Returns `true` if the scan shall stop
+    pub(crate) fn exhausted(&self) -> bool {
+        if self.truncated.get() == Some(&Truncation::Deadline) {
+            return true;
+        }
+        if self.deadline.is_some_and(|d| Instant::now() >= d) {
+            self.truncate(Truncation::Deadline);
+            return true;
+        }
+        if self
+            .max_matches
+            .is_some_and(|max| self.matches.load(Ordering::Relaxed) >= max)
+        {
+            self.truncate(Truncation::MaxMatches);
+            return true;
+        }
+        false
+    }
+
+    /// Count a match of `data`, returns `false` if it is over a limit and has to be dropped
+    pub(crate) fn take(&self, data: &Data) -> bool {
+        if let Some(max) = self.max_matches_per_pattern {
+            let mut per_pattern = self.per_pattern.lock().expect("never panics while locked");
+            let count = per_pattern.entry(data.id).or_default();
+            if *count >= max {
+                self.truncate(Truncation::MaxMatchesPerPattern);
+                return false;
+            }
+            *count += 1;
+        }
+        if let Some(max) = self.max_matches {
+            if self.matches.fetch_add(1, Ordering::Relaxed) >= max {
+                self.truncate(Truncation::MaxMatches);
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Count a scanned input along with the data of matches found in it,
+    /// then call `progress` with the counters so far.
+    pub(crate) fn record<'a>(
+        &self,
+        files: usize,
+        bytes: usize,
+        strings: usize,
+        found: impl Iterator<Item = &'a Data>,
+        progress: impl FnOnce(&ScanStats),
+    ) {
+        let mut stats = self.stats.lock().expect("progress callback panicked");
+        stats.files += files;
+        stats.bytes += bytes as u64;
+        stats.strings += strings;
+        found.for_each(|data| stats.add_data(data));
+        stats.truncated = self.truncated.get().copied();
+        progress(&stats);
+    }
+
+    /// Count a file which wasn't scanned, then call `progress` with the counters so far.
+    pub(crate) fn skip(&self, path: &str, reason: SkipReason, progress: impl FnOnce(&ScanStats)) {
+        let mut stats = self.stats.lock().expect("progress callback panicked");
+        stats.skipped.push(Skipped {
+            path: path.to_owned(),
+            reason,
+        });
+        progress(&stats);
+    }
+
+    /// Counters of the finished scan
+    pub(crate) fn finish(self) -> ScanStats {
+        let mut stats = self.stats.into_inner().expect("progress callback panicked");
+        // files are skipped in parallel
+        stats.skipped.sort_by(|a, b| a.path.cmp(&b.path));
+        stats.truncated = self.truncated.get().copied();
+        stats.elapsed = self.start.elapsed();
+        stats
+    }
+
+    fn truncate(&self, reason: Truncation) {
+        let _ = self.truncated.set(reason);
+    }
+}
+
+// seconds, e.g. `1.5`
+pub(crate) fn deserialize_secs<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<f64>::deserialize(deserializer)?
+        .map(Duration::try_from_secs_f64)
+        .transpose()
+        .map_err(serde::de::Error::custom)
+}
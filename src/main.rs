@@ -1,63 +1,755 @@
-use clap::Parser;
-use lemmeknow::{Identifier, PrintMode};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use std::{fs, io, process};
+
+use clap::{Parser, Subcommand};
+use lemmeknow::config::{Config, Format};
+use lemmeknow::normalize::Normalize;
+use lemmeknow::parallel::Parallelism;
+use lemmeknow::sniff::BinaryPolicy;
+use lemmeknow::tags::{TagExpr, TagMatch};
+use lemmeknow::{
+    baseline::{self, Baseline},
+    defang, output, pattern,
+    stats::ScanStats,
+    suppress, Data, Identifier, Match, MatchMode, PrintMode,
+};
+
+/// Exit code when findings matching `--fail-on` were found
+const EXIT_FINDINGS: i32 = 1;
+/// Exit code for invalid usage
+const EXIT_USAGE: i32 = 2;
+/// Exit code when a file can't be read or written
+const EXIT_IO: i32 = 3;
 
 #[derive(Parser)]
 #[clap(
     author,
     version,
     about,
-    long_about = "https://github.com/swanandx/lemmeknow"
+    long_about = "https://github.com/swanandx/lemmeknow",
+    subcommand_negates_reqs = true,
+    args_conflicts_with_subcommands = true
 )]
+struct Cli {
+    #[clap(subcommand)]
+    command: Option<Command>,
+    #[clap(flatten)]
+    args: Args,
+}
+
+#[derive(clap::Args)]
 struct Args {
-    /// Text which you want to identify
-    text: String,
+    #[clap(flatten)]
+    scan: ScanArgs,
     /// Output in JSON format
     #[clap(short, long)]
     json: bool,
-    /// Minimum Rarity
-    #[clap(long = "min", default_value_t = 0.1)]
-    min_rarity: f32,
-    /// Maximum Rarity
-    #[clap(long = "max", default_value_t = 1.0)]
-    max_rarity: f32,
+    /// Output JSON as an object with `matches`, `truncated` set to the limit which stopped the scan
+    /// or `null`, and `skipped` files, instead of an array of matches
+    #[clap(long)]
+    json_envelope: bool,
+    /// Print output with more details
+    #[clap(short, long)]
+    verbose: bool,
+    /// Sort findings from the most to the least likely identification,
+    /// by rarity, then length of literal prefix of the regex, then how much of the text is matched
+    #[clap(long)]
+    sort: bool,
+    /// Print a summary to stderr: files scanned, bytes processed, strings extracted,
+    /// matches per tag and elapsed time
+    #[clap(long)]
+    stats: bool,
+    /// Show a progress bar on stderr while scanning files, unless stdout isn't a terminal
+    #[clap(long)]
+    progress: bool,
+    /// Only report findings which are not present in this baseline file
+    #[clap(long)]
+    baseline: Option<PathBuf>,
+    /// Also report matches suppressed by `lemmeknow:allow` comments
+    #[clap(long)]
+    show_suppressed: bool,
+    /// Defang matched URLs, IPs and email addresses so they can't be clicked, e.g. `hxxps[://]evil[.]com`
+    #[clap(long)]
+    defang: bool,
+    /// Exit with code 1 if any finding matches one of these conditions:
+    /// `any`, `tag:<TAG>`, `rarity:<MIN>` or `confidence:<MIN>` (same as rarity)
+    #[clap(long, value_delimiter(','), value_name = "CONDITION")]
+    fail_on: Vec<FailOn>,
+}
+
+/// Condition for failing the scan when a qualifying finding is found
+#[derive(Clone)]
+enum FailOn {
+    /// Any finding
+    Any,
+    /// Findings having this tag
+    Tag(String),
+    /// Findings having at least this rarity
+    Rarity(f32),
+}
+
+impl FromStr for FailOn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rarity = |r: &str| {
+            r.parse()
+                .map(FailOn::Rarity)
+                .map_err(|_| format!("invalid rarity `{r}`"))
+        };
+
+        match s.split_once(':') {
+            Some(("tag", tag)) => Ok(FailOn::Tag(tag.to_owned())),
+            Some(("rarity" | "confidence", r)) => rarity(r),
+            _ if s == "any" => Ok(FailOn::Any),
+            _ => Err(format!(
+                "expected `any`, `tag:<TAG>`, `rarity:<MIN>` or `confidence:<MIN>`, found `{s}`"
+            )),
+        }
+    }
+}
+
+impl FailOn {
+    fn matches(&self, result: &Match) -> bool {
+        match self {
+            FailOn::Any => true,
+            FailOn::Tag(tag) => result.data.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)),
+            FailOn::Rarity(rarity) => result.data.rarity >= *rarity,
+        }
+    }
+}
+
+#[derive(clap::Args)]
+struct ScanArgs {
+    /// Text which you want to identify
+    #[clap(required_unless_present = "staged")]
+    text: Option<String>,
+    /// Minimum Rarity [default: 0.1]
+    #[clap(long = "min")]
+    min_rarity: Option<f32>,
+    /// Maximum Rarity [default: 1.0]
+    #[clap(long = "max")]
+    max_rarity: Option<f32>,
     /// Only identify text, Do not scan file
     #[clap(short, long)]
     text_only: bool,
-    /// Disable boundaryless mode, if you are getting lot of false positives
-    #[clap(short, long)]
+    /// Number of threads to scan files and diffs with, 1 scans sequentially [default: number of CPUs]
+    #[clap(long, value_name = "N")]
+    jobs: Option<usize>,
+    /// Stop scanning once this many matches are found
+    #[clap(long, value_name = "N")]
+    max_matches: Option<usize>,
+    /// Drop the matches of a pattern once it has this many
+    #[clap(long, value_name = "N")]
+    max_matches_per_pattern: Option<usize>,
+    /// Only scan this many bytes from the beginning of the text, every file or the diff
+    #[clap(long, value_name = "BYTES")]
+    max_input_size: Option<usize>,
+    /// Stop scanning after this many seconds, e.g. 1.5
+    #[clap(long, value_name = "SECONDS", value_parser = parse_secs)]
+    deadline: Option<Duration>,
+    /// How binary files are scanned: `skip`, `strings` (printable strings only) or `full` [default: strings]
+    #[clap(long, value_name = "POLICY")]
+    binary: Option<BinaryPolicy>,
+    /// Skip files larger than this, in bytes or with a K, M or G suffix, e.g. 100M
+    #[clap(long, value_name = "SIZE", value_parser = parse_size)]
+    max_filesize: Option<u64>,
+    /// Refang defanged indicators, e.g. `hxxps://evil[.]com`, before matching
+    #[clap(long)]
+    refang: bool,
+    /// Normalize text before matching, i.e. strip quotes and brackets around it,
+    /// remove zero-width and bidi characters and apply Unicode NFKC
+    #[clap(long)]
+    normalize: bool,
+    /// Join lines before matching, e.g. for base64 wrapped into lines
+    #[clap(long)]
+    join_lines: bool,
+    /// Disable boundaryless mode, if you are getting lot of false positives, same as `--mode anchored`
+    #[clap(short, long, conflicts_with = "mode")]
     boundary: bool,
+    /// How patterns have to match: `anchored` (whole text), `word` (delimited by whitespace,
+    /// quotes, `=`, `:`, brackets, etc.) or `boundaryless` (anywhere) [default: boundaryless]
+    #[clap(long, value_name = "MODE")]
+    mode: Option<MatchMode>,
     /// Include matches with these tags
     #[clap(short, long, value_delimiter(','))]
     include: Option<Vec<String>>,
     /// Exclude matches having these tags
     #[clap(short, long, value_delimiter(','))]
     exclude: Option<Vec<String>>,
-    /// Print output with more details
-    #[clap(short, long)]
-    verbose: bool,
+    /// Only include matches of patterns having these IDs, or names matching `--include-name`
+    #[clap(long, value_delimiter(','), value_name = "ID")]
+    include_id: Option<Vec<String>>,
+    /// Exclude matches of patterns having these IDs
+    #[clap(long, value_delimiter(','), value_name = "ID")]
+    exclude_id: Option<Vec<String>>,
+    /// Only include matches of patterns whose name matches these globs, e.g. "GitHub*"
+    #[clap(long, value_delimiter(','), value_name = "GLOB")]
+    include_name: Option<Vec<String>>,
+    /// Exclude matches of patterns whose name matches these globs
+    #[clap(long, value_delimiter(','), value_name = "GLOB")]
+    exclude_name: Option<Vec<String>>,
+    /// Whether matches need `any` or `all` of the included tags [default: any]
+    #[clap(long, value_name = "any|all")]
+    tag_match: Option<TagMatch>,
+    /// Only include matches whose tags satisfy this expression, e.g. "Credentials & !Bug Bounty"
+    #[clap(long, value_name = "EXPR")]
+    tags: Option<TagExpr>,
+    /// Treat input as a unified diff file and only scan added lines, use `-` to read it from stdin
+    #[clap(long)]
+    diff: bool,
+    /// Only scan lines added in staged changes, i.e. `git diff --cached`
+    #[clap(long, conflicts_with_all = ["diff", "text"])]
+    staged: bool,
+    /// Read settings from this file instead of `lemmeknow.toml` in current or parent directories
+    #[clap(long)]
+    config: Option<PathBuf>,
+    /// Use settings of this profile: ctf, secrets, pii, network, crypto or one defined in config
+    #[clap(long)]
+    profile: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Manage baseline of accepted findings
+    #[clap(subcommand)]
+    Baseline(BaselineCommand),
+    /// Inspect built-in patterns
+    #[clap(subcommand)]
+    Patterns(PatternsCommand),
+    /// Check built-in patterns and the ones from config against their examples
+    Selftest {
+        /// Read settings from this file instead of `lemmeknow.toml` in current or parent directories
+        #[clap(long)]
+        config: Option<PathBuf>,
+        /// Output in JSON format
+        #[clap(short, long)]
+        json: bool,
+    },
+    /// List all tags along with the number of patterns having them
+    Tags {
+        /// Output in JSON format, an array of `{"tag", "count"}` objects, most common first
+        #[clap(short, long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum PatternsCommand {
+    /// List ID, name, rarity and tags of patterns
+    List {
+        /// Only list patterns having these tags
+        #[clap(short, long, value_delimiter(','))]
+        tag: Vec<String>,
+        /// Minimum Rarity
+        #[clap(long = "min", default_value_t = 0.0)]
+        min_rarity: f32,
+        /// Maximum Rarity
+        #[clap(long = "max", default_value_t = 1.0)]
+        max_rarity: f32,
+        /// Output in JSON format
+        #[clap(short, long)]
+        json: bool,
+    },
+    /// Show regexes, description, URL, exploit and examples of a pattern
+    Show {
+        /// Name or ID of the pattern, case-insensitive
+        name: String,
+        /// Output in JSON format
+        #[clap(short, long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum BaselineCommand {
+    /// Accept all current findings by writing their fingerprints to a baseline file
+    Create {
+        #[clap(flatten)]
+        scan: Box<ScanArgs>,
+        /// Path of the baseline file
+        #[clap(short, long, default_value = ".lemmeknow-baseline.json")]
+        output: PathBuf,
+    },
+}
+
+impl ScanArgs {
+    /// Settings from the config file overridden by command line options.
+    ///
+    /// Exits if config or pattern files can't be read.
+    fn config(&self) -> Config {
+        let mut config = load_config(self.config.clone());
+
+        if let Some(profile) = self.profile.clone().or_else(|| config.profile.clone()) {
+            if let Err(e) = config.use_profile(&profile) {
+                exit_with(EXIT_USAGE, e);
+            }
+        }
+
+        let identifier = &mut config.identifier;
+        if let Some(min_rarity) = self.min_rarity {
+            identifier.min_rarity = min_rarity;
+        }
+        if let Some(max_rarity) = self.max_rarity {
+            identifier.max_rarity = max_rarity;
+        }
+        if let Some(include) = &self.include {
+            identifier.tags = include.clone();
+        }
+        if let Some(exclude) = &self.exclude {
+            identifier.exclude_tags = exclude.clone();
+        }
+        if let Some(ids) = &self.include_id {
+            identifier.ids = ids.clone();
+        }
+        if let Some(ids) = &self.exclude_id {
+            identifier.exclude_ids = ids.clone();
+        }
+        if let Some(names) = &self.include_name {
+            identifier.names = names.clone();
+        }
+        if let Some(names) = &self.exclude_name {
+            identifier.exclude_names = names.clone();
+        }
+        if let Some(tag_match) = self.tag_match {
+            identifier.tag_match = tag_match;
+        }
+        if let Some(expr) = &self.tags {
+            identifier.tag_expr = Some(expr.clone());
+        }
+        if self.boundary {
+            identifier.mode = MatchMode::Anchored;
+        }
+        if let Some(mode) = self.mode {
+            identifier.mode = mode;
+        }
+        if self.text_only {
+            identifier.file_support = false;
+        }
+        if let Some(jobs) = self.jobs {
+            identifier.parallelism = Parallelism::threads(jobs)
+                .unwrap_or_else(|e| exit_with(EXIT_USAGE, format!("--jobs {jobs}: {e}")));
+        }
+        if let Some(max) = self.max_matches {
+            identifier.max_matches = Some(max);
+        }
+        if let Some(max) = self.max_matches_per_pattern {
+            identifier.max_matches_per_pattern = Some(max);
+        }
+        if let Some(size) = self.max_input_size {
+            identifier.max_input_size = Some(size);
+        }
+        if let Some(deadline) = self.deadline {
+            identifier.deadline = Some(deadline);
+        }
+        if let Some(policy) = self.binary {
+            identifier.binary = policy;
+        }
+        if let Some(size) = self.max_filesize {
+            identifier.max_filesize = Some(size);
+        }
+        if self.refang {
+            identifier.refang = true;
+        }
+        if self.normalize {
+            identifier.normalize = Normalize {
+                join_lines: identifier.normalize.join_lines,
+                ..Normalize::pasted()
+            };
+        }
+        if self.join_lines {
+            identifier.normalize.join_lines = true;
+        }
+
+        config
+    }
+
+    fn text(&self) -> &str {
+        self.text.as_deref().unwrap_or_default()
+    }
+
+    /// Name of the input, for error messages
+    fn input_name(&self) -> &str {
+        match self.text() {
+            _ if self.staged => "staged changes",
+            "-" if self.diff => "stdin",
+            text => text,
+        }
+    }
+
+    /// Reported and suppressed matches along with stats of the scan, errors mention the input.
+    ///
+    /// Files and directories are scanned in parallel, showing a progress bar if `progress` is `true`.
+    /// Inline allow comments of a diff are read from the diff, since its line numbers may differ on disk.
+    fn identify(&self, config: &Config, progress: bool) -> io::Result<Identified> {
+        let identifier = &config.identifier;
+        let text = self.text();
+        let path = Path::new(text);
+        if identifier.file_support
+            && !self.staged
+            && !self.diff
+            && (path.is_file() || path.is_dir())
+        {
+            let skip = |path: &Path| config.is_ignored(&path.to_string_lossy());
+            let progress = progress && io::stdout().is_terminal();
+            let result = identifier.identify_paths_with(&[path], skip, |stats, total| {
+                if progress {
+                    output::print_progress(stats, total);
+                }
+            });
+            if progress {
+                // files left by a limit never complete the bar
+                output::clear_progress();
+            }
+            let (result, stats) = result?;
+            let (reported, suppressed) = suppress::partition(result);
+            return Ok((reported, suppressed, stats));
+        }
+
+        let with_input =
+            |e: io::Error| io::Error::new(e.kind(), format!("{}: {e}", self.input_name()));
+        let diff = if self.staged {
+            Some(staged_diff().map_err(with_input)?)
+        } else if self.diff {
+            let diff = if text == "-" {
+                io::read_to_string(io::stdin())
+            } else {
+                fs::read_to_string(text)
+            };
+            Some(diff.map_err(with_input)?)
+        } else {
+            None
+        };
+
+        let (mut result, stats) = match &diff {
+            Some(diff) => identifier.identify_diff_with_stats(diff),
+            None => identifier.identify_with_stats(text),
+        };
+        result.retain(|m| !m.path.as_deref().is_some_and(|p| config.is_ignored(p)));
+
+        let (reported, suppressed) = match &diff {
+            Some(diff) => suppress::partition_diff(result, diff),
+            None => suppress::partition(result),
+        };
+        Ok((reported, suppressed, stats))
+    }
+}
+
+/// Reported and suppressed matches, along with stats of the scan
+type Identified = (Vec<Match>, Vec<Match>, ScanStats);
+
+/// Read config from `path`, or the discovered `lemmeknow.toml`, along with its pattern files.
+///
+/// Exits if config or pattern files can't be read, or the config is invalid, e.g. has unknown keys.
+fn load_config(path: Option<PathBuf>) -> Config {
+    let mut config = match path.or_else(Config::discover) {
+        Some(path) => Config::load(&path).unwrap_or_else(|e| {
+            let code = match e.kind() {
+                io::ErrorKind::InvalidInput => EXIT_USAGE,
+                _ => EXIT_IO,
+            };
+            exit_with(
+                code,
+                format!("failed to read config from {}: {e}", path.display()),
+            )
+        }),
+        None => Config::default(),
+    };
+
+    for path in &config.patterns {
+        let patterns = pattern::load(path).unwrap_or_else(|e| {
+            exit_with(
+                EXIT_IO,
+                format!("failed to read patterns from {}: {e}", path.display()),
+            )
+        });
+        config.identifier.patterns.extend(patterns);
+    }
+
+    config
+}
+
+/// Diff of staged changes, with `a/` and `b/` prefixes regardless of git config.
+///
+/// One line of context is kept, for inline allow comments on the line before a change.
+fn staged_diff() -> io::Result<String> {
+    let output = process::Command::new("git")
+        .args([
+            "diff",
+            "--cached",
+            "--no-color",
+            "--no-ext-diff",
+            "--unified=1",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+        ])
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(
+            stderr.lines().next().unwrap_or_default().to_owned(),
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parse seconds, e.g. `1.5`
+fn parse_secs(s: &str) -> Result<Duration, String> {
+    s.parse::<f64>()
+        .map_err(|e| e.to_string())
+        .and_then(|secs| Duration::try_from_secs_f64(secs).map_err(|e| e.to_string()))
+}
+
+/// Parse size in bytes, e.g. `512`, `64K`, `100M` or `2G`
+fn parse_size(s: &str) -> Result<u64, String> {
+    let (number, unit) = match s.find(|c: char| c.is_ascii_alphabetic()) {
+        Some(i) => s.split_at(i),
+        None => (s, ""),
+    };
+    let shift = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => 0,
+        "K" | "KB" | "KIB" => 10,
+        "M" | "MB" | "MIB" => 20,
+        "G" | "GB" | "GIB" => 30,
+        _ => return Err(format!("expected a size like 100M, found `{s}`")),
+    };
+    number
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(1 << shift))
+        .ok_or_else(|| format!("expected a size like 100M, found `{s}`"))
+}
+
+/// Print error and exit with the given code
+fn exit_with(code: i32, message: impl Display) -> ! {
+    eprintln!("\x1b[0;31merror:\x1b[0m {message}");
+    process::exit(code)
 }
 
 fn main() {
-    let args = Args::parse();
-
-    let identifier = Identifier::default()
-        .min_rarity(args.min_rarity)
-        .max_rarity(args.max_rarity)
-        .include_tags(&args.include.unwrap_or_default())
-        .exclude_tags(&args.exclude.unwrap_or_default())
-        .boundaryless(!args.boundary) // boundaryless is true if boundary is false, and vice-versa
-        .file_support(!args.text_only); // file_support is true if text_only is false, and vice-versa
-
-    let result = identifier.identify(&args.text);
-    if args.json {
-        let result_in_json = Identifier::to_json(&result);
-        println!("{result_in_json}");
+    let cli = Cli::try_parse().unwrap_or_else(|e| {
+        let _ = e.print();
+        // --help and --version are not errors
+        process::exit(if e.use_stderr() { EXIT_USAGE } else { 0 })
+    });
+
+    match cli.command {
+        Some(Command::Baseline(BaselineCommand::Create { scan, output })) => {
+            let config = scan.config();
+            let (result, _, _) = scan
+                .identify(&config, false)
+                .unwrap_or_else(|e| exit_with(EXIT_IO, e));
+            let baseline = Baseline::from_matches_in(&result, baseline::parent_dir(&output));
+            if let Err(e) = baseline.save(&output) {
+                exit_with(
+                    EXIT_IO,
+                    format!("failed to write baseline to {}: {e}", output.display()),
+                );
+            }
+            println!(
+                "Wrote {} finding(s) to {}",
+                baseline.findings.len(),
+                output.display()
+            );
+        }
+        Some(Command::Patterns(PatternsCommand::List {
+            tag,
+            min_rarity,
+            max_rarity,
+            json,
+        })) => {
+            let patterns: Vec<&Data> = lemmeknow::patterns()
+                .iter()
+                .filter(|data| data.rarity >= min_rarity && data.rarity <= max_rarity)
+                .filter(|data| {
+                    tag.iter()
+                        .all(|t| data.tags.iter().any(|x| x.eq_ignore_ascii_case(t)))
+                })
+                .collect();
+
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&patterns).unwrap_or_default()
+                );
+            } else {
+                output::print_patterns(&patterns);
+                let dropped = lemmeknow::dropped_patterns();
+                if !dropped.is_empty() {
+                    println!(
+                        "Not available, regex isn't supported: {}",
+                        dropped.join(", ")
+                    );
+                }
+            }
+        }
+        Some(Command::Patterns(PatternsCommand::Show { name, json })) => {
+            let data =
+                lemmeknow::pattern_by_name(&name).or_else(|| lemmeknow::pattern_by_id(&name));
+            let Some(data) = data else {
+                let reason = if lemmeknow::dropped_patterns()
+                    .iter()
+                    .any(|n| n.eq_ignore_ascii_case(&name))
+                {
+                    "is not available, its regex isn't supported"
+                } else {
+                    "doesn't exist"
+                };
+                exit_with(EXIT_USAGE, format!("pattern `{name}` {reason}"));
+            };
+
+            if json {
+                let mut value = serde_json::to_value(data).unwrap_or_default();
+                value["boundaryless"] = data.boundaryless().into();
+                value["examples"] = serde_json::to_value(data.examples).unwrap_or_default();
+                println!("{value:#}");
+            } else {
+                output::print_pattern(data);
+            }
+        }
+        Some(Command::Tags { json }) => {
+            let mut tags: Vec<(&str, usize)> = lemmeknow::all_tags()
+                .into_iter()
+                .map(|tag| (tag, lemmeknow::patterns_with_tag(tag).count()))
+                .collect();
+            // most common first
+            tags.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+
+            if json {
+                let tags: Vec<_> = tags
+                    .iter()
+                    .map(|(tag, count)| serde_json::json!({ "tag": tag, "count": count }))
+                    .collect();
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&tags).unwrap_or_default()
+                );
+            } else {
+                output::print_tags(&tags);
+            }
+        }
+        Some(Command::Selftest { config, json }) => {
+            let identifier = load_config(config).identifier;
+            let failures = identifier.self_test();
+
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&failures).unwrap_or_default()
+                );
+            } else {
+                let count = lemmeknow::patterns().len() + identifier.patterns.len();
+                for failure in &failures {
+                    println!("\x1b[0;31mFAIL\x1b[0m {failure}");
+                }
+                println!(
+                    "Checked examples of {count} pattern(s), {} failure(s)",
+                    failures.len()
+                );
+            }
+
+            if !failures.is_empty() {
+                process::exit(EXIT_FINDINGS);
+            }
+        }
+        None => scan(cli.args),
+    }
+}
+
+fn scan(args: Args) {
+    let start = Instant::now();
+    let config = args.scan.config();
+    let (mut result, mut suppressed, stats) = args
+        .scan
+        .identify(&config, args.progress)
+        .unwrap_or_else(|e| exit_with(EXIT_IO, e));
+
+    if let Some(path) = &args.baseline {
+        match Baseline::load(path) {
+            Ok(baseline) => result = baseline.filter(result),
+            Err(e) => exit_with(
+                EXIT_IO,
+                format!("failed to read baseline from {}: {e}", path.display()),
+            ),
+        }
+    }
+
+    if args.sort {
+        config.identifier.sort(&mut result);
+    }
+
+    if args.defang {
+        result
+            .iter_mut()
+            .chain(&mut suppressed)
+            .for_each(defang::defang_match);
+    }
+
+    if args.json_envelope {
+        let mut result_in_json = serde_json::json!({
+            "matches": result,
+            "truncated": stats.truncated,
+            "skipped": stats.skipped,
+        });
+        if args.show_suppressed {
+            result_in_json["suppressed"] = serde_json::json!(suppressed);
+        }
+        println!("{result_in_json:#}");
+    } else if args.json || config.format == Format::Json {
+        if args.show_suppressed {
+            let result_in_json = serde_json::json!({ "matches": result, "suppressed": suppressed });
+            println!("{result_in_json:#}");
+        } else {
+            let result_in_json = Identifier::to_json(&result);
+            println!("{result_in_json}");
+        }
+        // keep the shape of JSON output the same, whether the scan was complete or not
+        for file in &stats.skipped {
+            eprintln!("Skipped {}: {}", file.path, file.reason);
+        }
+        if let Some(limit) = stats.truncated {
+            eprintln!("Results are truncated, {limit} was reached");
+        }
     } else {
-        let printer = if args.verbose {
+        let printer = if args.verbose || config.verbose {
             PrintMode::Verbose
         } else {
             PrintMode::Normal
         };
         printer.print(&result);
+        if args.show_suppressed {
+            printer.print_suppressed(&suppressed);
+        }
+        printer.print_skipped(&stats.skipped);
+        if let Some(limit) = stats.truncated {
+            printer.print_truncated(limit);
+        }
+    }
+
+    if args.stats {
+        // only count the reported matches
+        let mut summary = ScanStats {
+            matches: 0,
+            tags: BTreeMap::new(),
+            elapsed: start.elapsed(),
+            ..stats
+        };
+        summary.add_matches(&result);
+        eprintln!("{summary}");
+    }
+
+    if result
+        .iter()
+        .any(|m| args.fail_on.iter().any(|f| f.matches(m)))
+    {
+        process::exit(EXIT_FINDINGS);
     }
 }
@@ -0,0 +1,249 @@
+/*!
+ * For normalizing text before matching, so secrets pasted from chats or PDFs are still identified.
+ *
+ * Pasted text often has quotes or brackets around it, zero-width or bidi characters in it,
+ * fullwidth or other compatibility forms of characters, or is wrapped into lines, e.g. base64.
+ * Text is matched as it is by default. [`Normalize::pasted`] strips the wrapping, removes invisible
+ * characters and applies Unicode NFKC before matching, see [`Normalize`] for the steps.
+ *
+ * When text was changed, `original` of the match is the text as it was written
+ * and `offset` of a match in a file is where `original` starts, i.e. after the stripped wrapping.
+ *
+ * # Example:
+ *
+ * ```rust
+ * use lemmeknow::{normalize::Normalize, Identifier};
+ * let identifier = Identifier::default().include_ids(&["ipv4".to_owned()]).normalize(Normalize::pasted());
+ * let result = identifier.identify("\u{201c}127.0.0\u{200b}.1\u{201d}");
+ * assert_eq!(result[0].text, "127.0.0.1");
+ * assert_eq!(result[0].original.as_deref(), Some("127.0.0\u{200b}.1"));
+ * ```
+ */
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use serde::Deserialize;
+use unicode_normalization::{is_nfkc_quick, IsNormalized, UnicodeNormalization};
+
+// quotes and brackets which are stripped from around text
+const WRAPPING: &[(&str, &str)] = &[
+    ("\"", "\""),
+    ("'", "'"),
+    ("`", "`"),
+    ("\u{201c}", "\u{201d}"),
+    ("\u{201e}", "\u{201c}"),
+    ("\u{2018}", "\u{2019}"),
+    ("\u{00ab}", "\u{00bb}"),
+    ("(", ")"),
+    ("[", "]"),
+    ("{", "}"),
+    ("<", ">"),
+];
+
+// zero-width, bidi control characters and soft hyphen
+const INVISIBLE: &[&str] = &[
+    "\u{00ad}", "\u{061c}", "\u{180e}", "\u{200b}", "\u{200c}", "\u{200d}", "\u{200e}", "\u{200f}",
+    "\u{202a}", "\u{202b}", "\u{202c}", "\u{202d}", "\u{202e}", "\u{2060}", "\u{2066}", "\u{2067}",
+    "\u{2068}", "\u{2069}", "\u{feff}",
+];
+
+/// Steps applied to text before matching, in this order.
+///
+/// No step is applied by default. This can be deserialized, missing fields are `false`:
+///
+/// ```
+/// let normalize: lemmeknow::normalize::Normalize = serde_json::from_str(r#"{"join_lines": true}"#).unwrap();
+/// assert!(normalize.join_lines && !normalize.nfkc);
+/// ```
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct Normalize {
+    /// Strip whitespace, quotes and brackets around the text, e.g. `"token"`, `“token”` or `<token>`
+    pub strip_wrapping: bool,
+    /// Remove zero-width and bidi control characters, e.g. U+200B or U+202E
+    pub remove_invisible: bool,
+    /// Apply Unicode NFKC, e.g. fullwidth `ｓｋ＿` becomes `sk_`
+    pub nfkc: bool,
+    /// Join lines along with the whitespace around line breaks, e.g. for base64 wrapped into lines.
+    ///
+    /// Lines of files are joined when a line ends with a non-space character
+    /// and the next one starts with one, after at most 64 spaces or tabs of indentation.
+    pub join_lines: bool,
+}
+
+/// Text after normalization, along with the part of the input it came from
+pub struct Normalized<'a, T: ?Sized + ToOwned> {
+    /// Normalized text, borrowed if nothing but the wrapping was stripped
+    pub text: Cow<'a, T>,
+    /// Range of bytes of the input which `text` came from, i.e. without the stripped wrapping
+    pub span: Range<usize>,
+}
+
+impl Normalize {
+    /// No steps, text is matched as it is, same as [`Normalize::default`]
+    pub fn none() -> Normalize {
+        Normalize::default()
+    }
+
+    /// Steps for text pasted from chats or PDFs, i.e. all of them but `join_lines`
+    pub fn pasted() -> Normalize {
+        Normalize {
+            strip_wrapping: true,
+            remove_invisible: true,
+            nfkc: true,
+            join_lines: false,
+        }
+    }
+
+    /// Normalize text.
+    ///
+    /// ```
+    /// use lemmeknow::normalize::Normalize;
+    /// let normalized = Normalize::pasted().apply(" 'ＡＫＩＡ\u{200d}' ");
+    /// assert_eq!(normalized.text, "AKIA");
+    /// assert_eq!(normalized.span, 2..14);
+    /// ```
+    pub fn apply<'a>(&self, text: &'a str) -> Normalized<'a, str> {
+        let Normalized { text: bytes, span } = self.apply_bytes(text.as_bytes());
+        // only whole characters are stripped or removed, and NFKC is applied to valid UTF-8
+        let text = match bytes {
+            Cow::Borrowed(_) => Cow::Borrowed(&text[span.clone()]),
+            Cow::Owned(bytes) => {
+                Cow::Owned(String::from_utf8(bytes).expect("whole characters are changed"))
+            }
+        };
+        Normalized { text, span }
+    }
+
+    /// Same as [`Normalize::apply`], for bytes. NFKC is only applied if they are valid UTF-8.
+    pub fn apply_bytes<'a>(&self, text: &'a [u8]) -> Normalized<'a, [u8]> {
+        let span = if self.strip_wrapping {
+            self.unwrapped(text)
+        } else {
+            0..text.len()
+        };
+        let mut normalized = Cow::Borrowed(&text[span.clone()]);
+
+        if self.remove_invisible {
+            normalized = changed(normalized, remove_invisible);
+        }
+        if self.join_lines {
+            normalized = changed(normalized, join_lines);
+        }
+        if self.nfkc {
+            normalized = changed(normalized, nfkc);
+        }
+
+        Normalized {
+            text: normalized,
+            span,
+        }
+    }
+
+    // range of `text` inside of whitespace, invisible characters and wrapping
+    fn unwrapped(&self, text: &[u8]) -> Range<usize> {
+        let mut span = 0..text.len();
+
+        loop {
+            while let Some(len) = self.ignored_prefix(&text[span.clone()]) {
+                span.start += len;
+            }
+            while let Some(len) = self.ignored_suffix(&text[span.clone()]) {
+                span.end -= len;
+            }
+
+            let rest = &text[span.clone()];
+            match WRAPPING.iter().find(|(open, close)| {
+                rest.len() > open.len() + close.len()
+                    && rest.starts_with(open.as_bytes())
+                    && rest.ends_with(close.as_bytes())
+            }) {
+                Some((open, close)) => span = span.start + open.len()..span.end - close.len(),
+                None => return span,
+            }
+        }
+    }
+
+    // length of whitespace or an invisible character at the start of `text`
+    fn ignored_prefix(&self, text: &[u8]) -> Option<usize> {
+        match text.first() {
+            Some(b) if b.is_ascii_whitespace() => Some(1),
+            _ if self.remove_invisible => INVISIBLE
+                .iter()
+                .find(|c| text.starts_with(c.as_bytes()))
+                .map(|c| c.len()),
+            _ => None,
+        }
+    }
+
+    // length of whitespace or an invisible character at the end of `text`
+    fn ignored_suffix(&self, text: &[u8]) -> Option<usize> {
+        match text.last() {
+            Some(b) if b.is_ascii_whitespace() => Some(1),
+            _ if self.remove_invisible => INVISIBLE
+                .iter()
+                .find(|c| text.ends_with(c.as_bytes()))
+                .map(|c| c.len()),
+            _ => None,
+        }
+    }
+}
+
+// `text` changed by `f`, if `f` returns the changed text
+fn changed<'a>(text: Cow<'a, [u8]>, f: impl FnOnce(&[u8]) -> Option<Vec<u8>>) -> Cow<'a, [u8]> {
+    match f(&text) {
+        Some(changed) => Cow::Owned(changed),
+        None => text,
+    }
+}
+
+fn remove_invisible(text: &[u8]) -> Option<Vec<u8>> {
+    // every invisible character is encoded starting with one of these bytes
+    if !text
+        .iter()
+        .any(|&b| matches!(b, 0xc2 | 0xd8 | 0xe1 | 0xe2 | 0xef))
+    {
+        return None;
+    }
+
+    let mut removed: Option<Vec<u8>> = None;
+    let mut i = 0;
+    while i < text.len() {
+        match INVISIBLE
+            .iter()
+            .find(|c| text[i..].starts_with(c.as_bytes()))
+        {
+            Some(c) => {
+                removed.get_or_insert_with(|| text[..i].to_vec());
+                i += c.len();
+            }
+            None => {
+                if let Some(removed) = &mut removed {
+                    removed.push(text[i]);
+                }
+                i += 1;
+            }
+        }
+    }
+    removed
+}
+
+fn join_lines(text: &[u8]) -> Option<Vec<u8>> {
+    if !text.contains(&b'\n') {
+        return None;
+    }
+
+    let lines = text.split(|&b| b == b'\n');
+    Some(lines.flat_map(|line| line.trim_ascii()).copied().collect())
+}
+
+fn nfkc(text: &[u8]) -> Option<Vec<u8>> {
+    let text = std::str::from_utf8(text).ok()?;
+    if text.is_ascii() || is_nfkc_quick(text.chars()) == IsNormalized::Yes {
+        return None;
+    }
+
+    let normalized: String = text.nfkc().collect();
+    (normalized != text).then(|| normalized.into_bytes())
+}
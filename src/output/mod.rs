@@ -19,10 +19,14 @@
 use comfy_table::presets::UTF8_FULL;
 use comfy_table::*;
 
-use crate::Match;
+use crate::limits::Truncation;
+use crate::sniff::Skipped;
+use crate::stats::{human_bytes, ScanStats};
+use crate::{Data, Match};
 
 /// Modes defining how the output shall be printed
 /// > Requires `cli` feature
+#[derive(Clone, Copy)]
 pub enum PrintMode {
     Normal,
     Verbose,
@@ -55,11 +59,176 @@ impl PrintMode {
     pub fn print(self, result: &[Match]) {
         pretty_print(result, self)
     }
+
+    /// Print matches suppressed by inline allow comments in a tabular form.
+    ///
+    /// Prints nothing if there are no suppressed matches.
+    ///
+    /// # Arguments
+    ///
+    /// * suppressed: `&Match` - Reference to [`Vec<Match>`] of suppressed matches.
+    ///
+    pub fn print_suppressed(self, suppressed: &[Match]) {
+        if !suppressed.is_empty() {
+            println!("\x1b[0;33mSuppressed Identifications:\x1b[0m");
+            println!("{}", build_table(suppressed, self));
+        }
+    }
+
+    /// Print the files which weren't scanned along with the reason in a tabular form.
+    ///
+    /// Prints nothing if no file was skipped.
+    pub fn print_skipped(self, skipped: &[Skipped]) {
+        if !skipped.is_empty() {
+            let mut table = new_table(["Skipped file", "Reason"]);
+            for file in skipped {
+                table.add_row([file.path.clone(), file.reason.to_string()]);
+            }
+            println!("\x1b[0;33mSkipped Files:\x1b[0m");
+            println!("{table}");
+        }
+    }
+
+    /// Print a notice that the results are incomplete, because `limit` stopped the scan early.
+    pub fn print_truncated(self, limit: Truncation) {
+        println!("\x1b[0;33mResults are truncated, {limit} was reached\x1b[0m");
+    }
+}
+
+/// Print name, rarity and tags of the patterns in a tabular form.
+///
+/// > Requires `cli` feature
+pub fn print_patterns(patterns: &[&Data]) {
+    let mut table = new_table(["ID", "Name", "Rarity", "Tags"]);
+    patterns.iter().for_each(|data| {
+        table.add_row(vec![
+            Cell::new(data.id),
+            Cell::new(data.name),
+            Cell::new(data.rarity),
+            Cell::new(data.tags.join(", ")),
+        ]);
+    });
+    println!("{table}");
+}
+
+/// Print all details of a pattern, including its regexes and examples.
+///
+/// > Requires `cli` feature
+pub fn print_pattern(data: &Data) {
+    let optional = |value: Option<&str>| String::from(value.unwrap_or("None"));
+    let list = |values: &[&str]| {
+        if values.is_empty() {
+            String::from("None")
+        } else {
+            values.join("\n")
+        }
+    };
+
+    let mut table = new_table(["Field", "Value"]);
+    for (field, value) in [
+        ("ID", data.id.to_owned()),
+        ("Name", data.name.to_owned()),
+        ("Regex", data.regex.to_owned()),
+        ("Boundaryless regex", data.boundaryless().to_owned()),
+        ("Description", optional(data.description)),
+        ("URL", optional(data.url)),
+        ("Exploit", optional(data.exploit)),
+        ("Rarity", data.rarity.to_string()),
+        ("Tags", data.tags.join(", ")),
+        ("Valid examples", list(data.examples.valid)),
+        ("Invalid examples", list(data.examples.invalid)),
+    ] {
+        table.add_row(vec![
+            Cell::new(field).add_attribute(Attribute::Bold),
+            Cell::new(value),
+        ]);
+    }
+    println!("{table}");
+}
+
+/// Print tags along with number of patterns having them in a tabular form.
+///
+/// > Requires `cli` feature
+pub fn print_tags(tags: &[(&str, usize)]) {
+    let mut table = new_table(["Tag", "Patterns"]);
+    tags.iter().for_each(|(tag, count)| {
+        table.add_row(vec![Cell::new(tag), Cell::new(count)]);
+    });
+    println!("{table}");
+}
+
+/// Print a progress bar of a scan to stderr, replacing the previous one.
+///
+/// The bar is cleared once all files are scanned, or by [`clear_progress`].
+///
+/// > Requires `cli` feature
+pub fn print_progress(stats: &ScanStats, total: usize) {
+    const WIDTH: usize = 30;
+    let files = stats.files + stats.skipped.len();
+    let done = (files * WIDTH).checked_div(total).unwrap_or(WIDTH);
+    eprint!(
+        "\r\x1b[2K[{}{}] {files}/{total} file(s), {}",
+        "=".repeat(done),
+        " ".repeat(WIDTH - done),
+        human_bytes(stats.bytes)
+    );
+    if files >= total {
+        clear_progress();
+    }
+}
+
+/// Clear the progress bar, e.g. when a limit stopped the scan before all files were scanned.
+///
+/// > Requires `cli` feature
+pub fn clear_progress() {
+    eprint!("\r\x1b[2K");
+}
+
+fn new_table<const N: usize>(headers: [&str; N]) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(headers.map(|h| {
+            Cell::new(h)
+                .add_attribute(Attribute::Bold)
+                .fg(Color::Magenta)
+        }));
+    table
 }
 
 fn pretty_print(result: &[Match], output_format: PrintMode) {
+    if result.is_empty() {
+        println!("\x1b[0;31mNo Possible Identifications :(\x1b[0m");
+    } else {
+        println!("\x1b[0;32mFound Possible Identifications :)\x1b[0m");
+        println!("{}", build_table(result, output_format));
+    }
+}
+
+fn build_table(result: &[Match], output_format: PrintMode) -> Table {
     let mut table = Table::new();
-    let mut headers = vec![
+    // matches found in files or diffs have a location
+    let has_path = result.iter().any(|item| item.path.is_some());
+    let has_line = result.iter().any(|item| item.line.is_some());
+    let mut headers = vec![];
+
+    if has_path {
+        headers.push(
+            Cell::new("Path")
+                .add_attribute(Attribute::Bold)
+                .fg(Color::Magenta),
+        );
+    }
+    if has_line {
+        headers.push(
+            Cell::new("Line")
+                .add_attribute(Attribute::Bold)
+                .fg(Color::Magenta),
+        );
+    }
+
+    headers.extend([
         Cell::new("Matched text")
             .add_attribute(Attribute::Bold)
             .fg(Color::Magenta),
@@ -69,7 +238,7 @@ fn pretty_print(result: &[Match], output_format: PrintMode) {
         Cell::new("Description")
             .add_attribute(Attribute::Bold)
             .fg(Color::Magenta),
-    ];
+    ]);
 
     if let PrintMode::Verbose = output_format {
         headers.extend([
@@ -88,42 +257,47 @@ fn pretty_print(result: &[Match], output_format: PrintMode) {
         // .set_table_width(80)
         .set_header(headers);
 
-    if result.is_empty() {
-        println!("\x1b[0;31mNo Possible Identifications :(\x1b[0m");
-    } else {
-        println!("\x1b[0;32mFound Possible Identifications :)\x1b[0m");
+    result.iter().for_each(|item| {
+        let mut description = String::from(item.data.description.unwrap_or_default());
 
-        result.iter().for_each(|item| {
-            let mut description = String::from(item.data.description.unwrap_or_default());
+        if let Some(url) = item.data.url {
+            description.push_str(&format!(" URL: {url}{}\n", &item.text))
+        }
 
-            if let Some(url) = item.data.url {
-                description.push_str(&format!(" URL: {url}{}\n", &item.text))
-            }
+        if let Some(exploit) = item.data.exploit {
+            description.push_str(&format!("Exploit: {exploit}"))
+        }
 
-            if let Some(exploit) = item.data.exploit {
-                description.push_str(&format!("Exploit: {exploit}"))
-            }
+        if description.is_empty() {
+            description.push_str("None")
+        }
 
-            if description.is_empty() {
-                description.push_str("None")
-            }
+        let mut row = vec![];
 
-            let mut row = vec![
-                Cell::new(&item.text),
-                Cell::new(item.data.name),
-                Cell::new(description),
-            ];
-
-            if let PrintMode::Verbose = output_format {
-                row.extend([
-                    Cell::new(item.data.rarity),
-                    Cell::new(item.data.tags.join(", ")),
-                ]);
-            }
+        if has_path {
+            row.push(Cell::new(item.path.as_deref().unwrap_or_default()));
+        }
+        if has_line {
+            row.push(Cell::new(
+                item.line.map(|line| line.to_string()).unwrap_or_default(),
+            ));
+        }
+
+        row.extend([
+            Cell::new(&item.text),
+            Cell::new(item.data.name),
+            Cell::new(description),
+        ]);
 
-            table.add_row(row);
-        });
+        if let PrintMode::Verbose = output_format {
+            row.extend([
+                Cell::new(item.data.rarity),
+                Cell::new(item.data.tags.join(", ")),
+            ]);
+        }
 
-        println!("{table}");
-    }
+        table.add_row(row);
+    });
+
+    table
 }
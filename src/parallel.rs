@@ -0,0 +1,91 @@
+/*!
+ * For choosing how files and diffs are scanned in parallel.
+ *
+ * By default scans run on rayon's global thread pool. When lemmeknow is embedded in a service
+ * having its own thread pools, use a dedicated number of threads, your own pool, or scan sequentially.
+ * Identifying a single text never uses threads.
+ *
+ * Without the `parallel` feature (enabled by default), rayon is not a dependency and
+ * everything is scanned sequentially.
+ *
+ * # Example:
+ *
+ * ```rust
+ * use lemmeknow::{parallel::Parallelism, Identifier};
+ * let identifier = Identifier::default().threads(1).unwrap();
+ * assert!(matches!(identifier.parallelism, Parallelism::Sequential));
+ *
+ * let (_, stats) = identifier.identify_paths(&["src/data"]).unwrap();
+ * assert!(stats.files > 0);
+ * ```
+ */
+
+use std::io;
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+use {
+    rayon::{
+        iter::{IntoParallelRefIterator, ParallelIterator},
+        ThreadPool, ThreadPoolBuilder,
+    },
+    std::sync::Arc,
+};
+
+use serde::{Deserialize, Deserializer};
+
+/// Where scans are run, read from number of threads, see [`Parallelism::threads`]
+#[derive(Debug, Clone, Default)]
+pub enum Parallelism {
+    /// rayon's global thread pool, which has a thread per CPU unless configured otherwise
+    #[default]
+    Global,
+    /// Only the calling thread
+    Sequential,
+    /// The given thread pool
+    #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+    Pool(Arc<ThreadPool>),
+}
+
+impl Parallelism {
+    /// `0` is [`Parallelism::Global`], `1` is [`Parallelism::Sequential`],
+    /// any other number is a new thread pool having that many threads.
+    ///
+    /// Without the `parallel` feature, it is always sequential.
+    ///
+    /// Returns an error if the thread pool can't be created.
+    pub fn threads(n: usize) -> io::Result<Parallelism> {
+        match n {
+            0 => Ok(Parallelism::Global),
+            1 => Ok(Parallelism::Sequential),
+            #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+            n => ThreadPoolBuilder::new()
+                .num_threads(n)
+                .build()
+                .map(|pool| Parallelism::Pool(Arc::new(pool)))
+                .map_err(io::Error::other),
+            #[cfg(not(all(feature = "parallel", not(target_arch = "wasm32"))))]
+            _ => Ok(Parallelism::Sequential),
+        }
+    }
+
+    /// Call `f` for every item and collect the results in order.
+    pub(crate) fn map<T, R, F>(&self, items: &[T], f: F) -> Vec<R>
+    where
+        T: Sync,
+        R: Send,
+        F: Fn(&T) -> R + Sync + Send,
+    {
+        match self {
+            #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+            Parallelism::Global => items.par_iter().map(f).collect(),
+            #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+            Parallelism::Pool(pool) => pool.install(|| items.par_iter().map(f).collect()),
+            _ => items.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Parallelism {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Parallelism::threads(usize::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,278 @@
+/*!
+ * For identifying text with patterns which are not built into lemmeknow.
+ *
+ * Pattern files use the same JSON format as the built-in patterns,
+ * i.e. an array of objects having `Name`, `Regex`, `Rarity` and optionally
+ * `ID`, `BoundarylessRegex`, `plural_name`, `Description`, `Exploit`, `URL`, `Tags` and `Examples`.
+ * If `ID` is missing, it is made from the name, e.g. `internal-api-token`.
+ * If `BoundarylessRegex` is missing, it is made by removing `^` and `$` anchors from `Regex`,
+ * which doesn't work for every regex, e.g. `^a|ab$`. Use `lemmeknow selftest` to check.
+ *
+ * ```json
+ * [
+ *   {
+ *     "Name": "Internal API Token",
+ *     "Regex": "^itk_[a-z0-9]{32}$",
+ *     "Rarity": 1,
+ *     "Tags": ["Credentials"]
+ *   }
+ * ]
+ * ```
+ *
+ * Pattern data lives for rest of the program, just like the built-in patterns.
+ * Each distinct string is kept only once, so loading the same file again doesn't use more memory,
+ * but loading ever different patterns does.
+ *
+ * # Example:
+ *
+ * ```rust
+ * use lemmeknow::{pattern, Identifier};
+ * let patterns = pattern::from_json(r#"[{"Name": "Internal API Token", "Regex": "^itk_[a-z0-9]{32}$", "Rarity": 1}]"#).unwrap();
+ * let identifier = Identifier::default().patterns(patterns);
+ *
+ * let result = identifier.identify("itk_0123456789abcdef0123456789abcdef");
+ * assert_eq!(result[0].data.name, "Internal API Token");
+ * ```
+ */
+
+use std::collections::HashSet;
+use std::io;
+use std::sync::{Mutex, PoisonError};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{fs, path::Path};
+
+use once_cell::sync::{Lazy, OnceCell};
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::anchors::remove_anchors;
+use crate::{Data, Examples, MatchMode};
+
+#[derive(Deserialize)]
+struct PatternData {
+    #[serde(rename = "ID")]
+    id: Option<String>,
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Regex")]
+    regex: String,
+    #[serde(rename = "BoundarylessRegex")]
+    boundaryless: Option<String>,
+    #[serde(default)]
+    plural_name: bool,
+    #[serde(rename = "Description")]
+    description: Option<String>,
+    #[serde(rename = "Exploit")]
+    exploit: Option<String>,
+    #[serde(rename = "Rarity")]
+    rarity: f32,
+    #[serde(rename = "URL")]
+    url: Option<String>,
+    #[serde(rename = "Tags", default)]
+    tags: Vec<String>,
+    #[serde(rename = "Examples", default)]
+    examples: PatternExamples,
+}
+
+#[derive(Deserialize, Default)]
+struct PatternExamples {
+    #[serde(rename = "Valid", default)]
+    valid: Vec<String>,
+    #[serde(rename = "Invalid", default)]
+    invalid: Vec<String>,
+}
+
+/// A pattern along with its compiled regexes.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    pub data: Data,
+    regex: Regex,
+    word: Regex,
+    boundaryless: Regex,
+    /// Same regexes for bytes, compiled on first use
+    bytes: OnceCell<[regex::bytes::Regex; 3]>,
+}
+
+impl Pattern {
+    /// Compile the regexes of the given data.
+    ///
+    /// Unless `data` already has one, boundaryless regex is made by removing the `^` and `$` anchors
+    /// which are neither escaped nor inside a character class, and word mode regex by surrounding it
+    /// with delimiters, using the same code as for built-in patterns.
+    pub fn new(mut data: Data) -> Result<Pattern, regex::Error> {
+        if data.boundaryless.is_empty() {
+            data.boundaryless = leak(remove_anchors(data.regex));
+        }
+        Ok(Pattern {
+            regex: Regex::new(data.regex)?,
+            word: Regex::new(&word_bounded(data.boundaryless))?,
+            boundaryless: Regex::new(data.boundaryless)?,
+            bytes: OnceCell::new(),
+            data,
+        })
+    }
+
+    /// Regex to be used in the given mode
+    #[inline]
+    pub fn regex(&self, mode: MatchMode) -> &Regex {
+        match mode {
+            MatchMode::Anchored => &self.regex,
+            MatchMode::Word => &self.word,
+            MatchMode::Boundaryless => &self.boundaryless,
+        }
+    }
+
+    /// Regex to be used in the given mode for bytes, see [`bytes`](crate::bytes)
+    pub fn bytes_regex(&self, mode: MatchMode) -> &regex::bytes::Regex {
+        let [regex, word, boundaryless] = self.bytes.get_or_init(|| {
+            [&self.regex, &self.word, &self.boundaryless].map(|r| {
+                regex::bytes::Regex::new(r.as_str()).expect("regex for str is valid for bytes too")
+            })
+        });
+        match mode {
+            MatchMode::Anchored => regex,
+            MatchMode::Word => word,
+            MatchMode::Boundaryless => boundaryless,
+        }
+    }
+}
+
+/// Changes to a built-in pattern, see [`Identifier::override_pattern`](crate::Identifier::override_pattern).
+///
+/// Fields which are `None` are left as they are.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Override {
+    pub rarity: Option<f32>,
+    pub tags: Option<Vec<String>>,
+    pub description: Option<String>,
+    /// Regex to use instead, boundaryless regex is made from it by removing the anchors
+    /// unless `boundaryless_regex` is given too
+    pub regex: Option<String>,
+    /// Boundaryless regex to use instead
+    pub boundaryless_regex: Option<String>,
+    /// Don't use the pattern at all
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+impl Override {
+    /// Pattern made by applying the changes to `data`.
+    ///
+    /// Returns an error with [`io::ErrorKind::InvalidData`] if the regex fails to compile.
+    ///
+    /// Like data of [loaded patterns](self), the given strings live for rest of the program.
+    /// Each distinct string is kept only once, so applying the same override again doesn't use more memory.
+    pub fn apply(self, data: &Data) -> io::Result<Pattern> {
+        let mut data = data.clone();
+        if let Some(rarity) = self.rarity {
+            data.rarity = rarity;
+        }
+        if let Some(tags) = self.tags {
+            data.tags = leak_all(tags);
+        }
+        if let Some(description) = self.description {
+            data.description = Some(leak(description));
+        }
+        if let Some(regex) = self.regex {
+            data.regex = leak(regex);
+            data.boundaryless = "";
+        }
+        if let Some(boundaryless) = self.boundaryless_regex {
+            data.boundaryless = leak(boundaryless);
+        }
+
+        let name = data.name;
+        Pattern::new(data).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid regex for {name}: {e}"),
+            )
+        })
+    }
+}
+
+/// Parse patterns from JSON.
+///
+/// Returns an error with [`io::ErrorKind::InvalidData`] if JSON is invalid or any regex fails to compile.
+pub fn from_json(json: &str) -> io::Result<Vec<Pattern>> {
+    let patterns: Vec<PatternData> =
+        serde_json::from_str(json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    patterns
+        .into_iter()
+        .map(|p| {
+            let name = p.name.clone();
+            Pattern::new(Data {
+                id: leak(p.id.unwrap_or_else(|| slug(&p.name))),
+                name: leak(p.name),
+                regex: leak(p.regex),
+                boundaryless: p.boundaryless.map_or("", leak),
+                plural_name: p.plural_name,
+                description: p.description.map(leak),
+                exploit: p.exploit.map(leak),
+                rarity: p.rarity,
+                url: p.url.map(leak),
+                tags: leak_all(p.tags),
+                examples: Examples {
+                    valid: leak_all(p.examples.valid),
+                    invalid: leak_all(p.examples.invalid),
+                },
+            })
+            .map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid regex for {name}: {e}"),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Read patterns from JSON file.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load(path: impl AsRef<Path>) -> io::Result<Vec<Pattern>> {
+    from_json(&fs::read_to_string(path)?)
+}
+
+// lowercase alphanumeric words joined by `-`, e.g. `internal-api-token`
+fn slug(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+// strings leaked so far, each distinct string is leaked only once so that memory doesn't grow
+// when the same patterns or overrides are loaded again, e.g. for every request of a service
+static LEAKED: Lazy<Mutex<HashSet<&'static str>>> = Lazy::new(Default::default);
+static LEAKED_LISTS: Lazy<Mutex<HashSet<&'static [&'static str]>>> = Lazy::new(Default::default);
+
+fn leak(s: impl Into<String>) -> &'static str {
+    let s = s.into();
+    let mut leaked = LEAKED.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(&s) = leaked.get(s.as_str()) {
+        return s;
+    }
+    let s = String::leak(s);
+    leaked.insert(s);
+    s
+}
+
+fn leak_all(strings: Vec<String>) -> &'static [&'static str] {
+    let strings: Vec<&'static str> = strings.into_iter().map(leak).collect();
+    let mut leaked = LEAKED_LISTS.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(&strings) = leaked.get(strings.as_slice()) {
+        return strings;
+    }
+    let strings = Vec::leak(strings);
+    leaked.insert(strings);
+    strings
+}
+
+// delimiters allowed around a match in word mode, same as in build.rs
+const DELIMITERS: &str = r#"[\s"'`=:,;()\[\]{}<>]"#;
+
+fn word_bounded(boundaryless: &str) -> String {
+    format!("(?:^|{DELIMITERS})(?:{boundaryless})(?:$|{DELIMITERS})")
+}
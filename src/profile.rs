@@ -0,0 +1,124 @@
+/*!
+ * Built-in profiles for common use cases.
+ *
+ * A profile preconfigures rarity range, tags and match mode of an [`Identifier`].
+ *
+ * | Profile | Finds |
+ * | --- | --- |
+ * | `ctf` | everything, even inside other text |
+ * | `secrets` | credentials and API keys |
+ * | `pii` | personal information like email addresses, phone numbers and dates of birth |
+ * | `network` | IP and MAC addresses, AWS resources and other networking identifiers |
+ * | `crypto` | cryptocurrency wallet addresses |
+ *
+ * # Example:
+ *
+ * ```rust
+ * use lemmeknow::profile::Profile;
+ * let profile: Profile = "secrets".parse().unwrap();
+ * let identifier = profile.identifier();
+ *
+ * assert!(identifier.identify("hello@example.com").is_empty());
+ * ```
+ */
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::Identifier;
+
+/// Built-in profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    Ctf,
+    Secrets,
+    Pii,
+    Network,
+    Crypto,
+}
+
+impl Profile {
+    /// All built-in profiles
+    pub const ALL: [Profile; 5] = [
+        Profile::Ctf,
+        Profile::Secrets,
+        Profile::Pii,
+        Profile::Network,
+        Profile::Crypto,
+    ];
+
+    /// Name of the profile, as accepted by [`Profile::from_str`]
+    pub fn name(self) -> &'static str {
+        match self {
+            Profile::Ctf => "ctf",
+            Profile::Secrets => "secrets",
+            Profile::Pii => "pii",
+            Profile::Network => "network",
+            Profile::Crypto => "crypto",
+        }
+    }
+
+    /// [`Identifier`] configured for this profile.
+    ///
+    /// Only rarity range, tags and match mode are set, everything else has default value.
+    pub fn identifier(self) -> Identifier {
+        let tags = |tags: &[&str]| -> Vec<String> { tags.iter().map(|t| t.to_string()).collect() };
+
+        match self {
+            Profile::Ctf => Identifier::default().boundaryless(true),
+            Profile::Secrets => Identifier::default()
+                .min_rarity(0.1)
+                .include_tags(&tags(&["Credentials"]))
+                // emails, phone numbers, etc. are PII, not secrets
+                .exclude_tags(&tags(&["Identifiers"]))
+                .boundaryless(true),
+            Profile::Pii => Identifier::default()
+                .include_tags(&tags(&["Identifiers"]))
+                .exclude_tags(&tags(&[
+                    "Networking",
+                    "AWS",
+                    "Amazon",
+                    "API Keys",
+                    "Media",
+                    "URL",
+                    "UUID",
+                ])),
+            Profile::Network => Identifier::default()
+                .include_tags(&tags(&["Networking"]))
+                .boundaryless(true),
+            Profile::Crypto => {
+                Identifier::default().include_tags(&tags(&["Cryptocurrency Wallet"]))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Profile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Profile::ALL
+            .into_iter()
+            .find(|p| p.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let names: Vec<_> = Profile::ALL.iter().map(|p| p.name()).collect();
+                format!(
+                    "unknown profile `{s}`, expected one of {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl From<Profile> for Identifier {
+    #[inline]
+    fn from(profile: Profile) -> Self {
+        profile.identifier()
+    }
+}
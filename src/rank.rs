@@ -0,0 +1,171 @@
+/*!
+ * For ranking identifications from the most to the least likely one.
+ *
+ * Matches are compared by, in this order:
+ *
+ * 1. `rarity` of the pattern, higher first
+ * 2. length of the literal prefix of the regex, e.g. `ghp_` of GitHub Personal Access Token, longer first
+ * 3. coverage, i.e. how much of the text is covered by the match, more first
+ *    (always all of it in anchored mode)
+ *
+ * Matches which are still tied keep the order in which patterns are tried,
+ * i.e. the order of built-in patterns followed by the added ones.
+ *
+ * # Example:
+ *
+ * ```rust
+ * use lemmeknow::rank::literal_prefix_len;
+ *
+ * assert_eq!(literal_prefix_len("^ghp_[0-9a-zA-Z]{36}$"), 4);
+ * assert_eq!(literal_prefix_len(r"^(?:https?://)?example\.com$"), 0);
+ * assert_eq!(literal_prefix_len("^abc|xyz$"), 0);
+ * ```
+ */
+
+use std::cmp::Ordering;
+
+use crate::Data;
+
+/// Rank of a match, greater is better.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rank {
+    pub rarity: f32,
+    /// Length of the literal prefix of the regex, see [`literal_prefix_len`]
+    pub prefix_len: usize,
+    /// Fraction of the text covered by the match, from 0 to 1
+    pub coverage: f32,
+}
+
+impl Rank {
+    /// Rank of a match of `data` spanning `span` bytes out of `len` bytes of text.
+    pub fn new(data: &Data, span: usize, len: usize) -> Rank {
+        Rank {
+            rarity: data.rarity,
+            prefix_len: literal_prefix_len(data.regex),
+            coverage: if len == 0 {
+                1.0
+            } else {
+                span as f32 / len as f32
+            },
+        }
+    }
+}
+
+impl Eq for Rank {}
+
+impl PartialOrd for Rank {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rank {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rarity
+            .total_cmp(&other.rarity)
+            .then(self.prefix_len.cmp(&other.prefix_len))
+            .then(self.coverage.total_cmp(&other.coverage))
+    }
+}
+
+/// Number of characters every match of `regex` starts with.
+///
+/// Leading `^`, inline flags like `(?i)` and non-optional groups are skipped,
+/// counting stops at the first character which isn't a literal.
+/// Always 0 if `regex` has alternatives at its top level, e.g. `^abc|xyz$`.
+pub fn literal_prefix_len(regex: &str) -> usize {
+    if let Some((_, '|')) = top_level_end(regex) {
+        return 0;
+    }
+    let mut rest = regex;
+    let mut len = 0;
+
+    loop {
+        if let Some(after) = rest.strip_prefix('^').filter(|_| len == 0) {
+            rest = after;
+            continue;
+        }
+        // inline flags and groups are transparent, unless they are optional or have alternatives
+        if let Some(after) = rest.strip_prefix("(?") {
+            match after.find(')') {
+                Some(end)
+                    if after[..end]
+                        .chars()
+                        .all(|c| c.is_ascii_alphabetic() || c == '-') =>
+                {
+                    rest = &after[end + 1..];
+                    continue;
+                }
+                _ => {}
+            }
+            match after.strip_prefix(':') {
+                Some(group) if is_required_group(group) => {
+                    rest = group;
+                    continue;
+                }
+                _ => return len,
+            }
+        }
+        if let Some(group) = rest.strip_prefix('(') {
+            if is_required_group(group) {
+                rest = group;
+                continue;
+            }
+            return len;
+        }
+
+        let mut chars = rest.chars();
+        let literal_len = match chars.next() {
+            Some('\\') => match chars.next() {
+                Some(c) if c.is_ascii_punctuation() => 2,
+                _ => return len,
+            },
+            Some(c) if !"[](){}|.*+?$^".contains(c) => c.len_utf8(),
+            _ => return len,
+        };
+
+        // a quantifier makes the literal optional or repeated
+        let next = &rest[literal_len..];
+        if next.starts_with(['*', '?', '{']) {
+            return len;
+        }
+        len += 1;
+        rest = next;
+        if next.starts_with('+') {
+            return len;
+        }
+    }
+}
+
+// `group` is the text following `(`, the group is required if it has no
+// alternatives at its top level and is not followed by an optional quantifier
+fn is_required_group(group: &str) -> bool {
+    match top_level_end(group) {
+        Some((i, ')')) => !group[i + 1..].starts_with(['*', '?', '{']),
+        _ => false,
+    }
+}
+
+// position of the first `|` or unmatched `)` in `text` which is neither escaped,
+// inside a character class nor inside a nested group
+fn top_level_end(text: &str) -> Option<(usize, char)> {
+    let mut depth = 0;
+    let mut escaped = false;
+    let mut in_class = false;
+
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '[' => in_class = true,
+            ']' => in_class = false,
+            _ if in_class => {}
+            '(' => depth += 1,
+            '|' | ')' if depth == 0 => return Some((i, c)),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
+}
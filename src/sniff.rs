@@ -0,0 +1,213 @@
+/*!
+ * For telling binary files apart from text files, and choosing how to scan them.
+ *
+ * Content is binary if it starts with the magic bytes of a known format, e.g. an executable,
+ * archive, image or video, or if more than 1% of its first 8 KiB are NUL bytes.
+ * Magic bytes which are plain letters, e.g. `MZ` or `RIFF`, only count if the content isn't text,
+ * i.e. its first 8 KiB have a NUL byte or aren't valid UTF-8.
+ *
+ * How binary files are scanned is set by [`BinaryPolicy`], and files larger than
+ * `max_filesize` are skipped. Skipped files are reported along with the reason in
+ * [`ScanStats::skipped`](crate::stats::ScanStats::skipped).
+ *
+ * # Example:
+ *
+ * ```rust
+ * use lemmeknow::sniff::{sniff, Binary};
+ * assert_eq!(sniff(b"\x7fELF\x02\x01\x01"), Some(Binary::Format("ELF executable")));
+ * assert_eq!(sniff(b"\x00\x00\x00\x01"), Some(Binary::NulBytes));
+ * assert_eq!(sniff("naïve text\n".as_bytes()), None);
+ * assert_eq!(sniff(b"MZ\x90\x00\x03\x00"), Some(Binary::Format("PE executable")));
+ * assert_eq!(sniff(b"MZ is a postal code\n"), None);
+ * ```
+ */
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::stats::human_bytes;
+
+/// Number of bytes from the start of the content checked for NUL bytes
+const SAMPLE_SIZE: usize = 8 * 1024;
+
+/// Content with more NUL bytes than this in its sample is binary
+const MAX_NUL_RATIO: f64 = 0.01;
+
+// offset, magic bytes and name of the format
+const MAGIC: &[(usize, &[u8], &str)] = &[
+    (0, b"\x7fELF", "ELF executable"),
+    (0, b"\xfe\xed\xfa\xce", "Mach-O executable"),
+    (0, b"\xfe\xed\xfa\xcf", "Mach-O executable"),
+    (0, b"\xce\xfa\xed\xfe", "Mach-O executable"),
+    (0, b"\xcf\xfa\xed\xfe", "Mach-O executable"),
+    (
+        0,
+        b"\xca\xfe\xba\xbe",
+        "Mach-O universal binary or Java class",
+    ),
+    (0, b"\x00asm", "WebAssembly module"),
+    (0, b"PK\x03\x04", "ZIP archive"),
+    (0, b"\x1f\x8b", "gzip archive"),
+    (0, b"\xfd7zXZ\x00", "xz archive"),
+    (0, b"\x28\xb5\x2f\xfd", "zstd archive"),
+    (0, b"7z\xbc\xaf\x27\x1c", "7z archive"),
+    (0, b"Rar!\x1a\x07", "RAR archive"),
+    (257, b"ustar", "tar archive"),
+    (0x8001, b"CD001", "ISO 9660 image"),
+    (0, b"SQLite format 3\x00", "SQLite database"),
+    (0, b"%PDF-", "PDF document"),
+    (0, b"\x89PNG\r\n\x1a\n", "PNG image"),
+    (0, b"\xff\xd8\xff", "JPEG image"),
+    (0, b"GIF87a", "GIF image"),
+    (0, b"GIF89a", "GIF image"),
+    (4, b"ftyp", "MP4 media"),
+    (0, b"\x1a\x45\xdf\xa3", "Matroska media"),
+    (0, b"OggS", "Ogg media"),
+    (0, b"fLaC", "FLAC audio"),
+];
+
+// magic bytes which text could start with, only used if the content isn't text
+const WEAK_MAGIC: &[(usize, &[u8], &str)] = &[
+    (0, b"MZ", "PE executable"),
+    (0, b"BZh", "bzip2 archive"),
+    (0, b"RIFF", "RIFF media"),
+    (0, b"ID3", "MP3 audio"),
+];
+
+/// Why content is considered binary
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Binary {
+    /// Starts with the magic bytes of this format
+    Format(&'static str),
+    /// Has too many NUL bytes to be text
+    NulBytes,
+}
+
+impl fmt::Display for Binary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Binary::Format(format) => f.write_str(format),
+            Binary::NulBytes => f.write_str("contains NUL bytes"),
+        }
+    }
+}
+
+/// Guess whether the content is binary, `None` if it looks like text.
+pub fn sniff(content: &[u8]) -> Option<Binary> {
+    if let Some(format) = format(MAGIC, content) {
+        return Some(Binary::Format(format));
+    }
+
+    let sample = &content[..content.len().min(SAMPLE_SIZE)];
+    let nul = sample.iter().filter(|&&b| b == 0).count();
+    // the sample may end in the middle of a character
+    let utf8 = std::str::from_utf8(sample).map_or_else(|e| e.error_len().is_none(), |_| true);
+    if let Some(format) = format(WEAK_MAGIC, content).filter(|_| nul > 0 || !utf8) {
+        return Some(Binary::Format(format));
+    }
+
+    (nul as f64 > sample.len() as f64 * MAX_NUL_RATIO).then_some(Binary::NulBytes)
+}
+
+// format whose magic bytes the content has
+fn format(magic: &[(usize, &[u8], &'static str)], content: &[u8]) -> Option<&'static str> {
+    magic
+        .iter()
+        .find(|(offset, magic, _)| {
+            content
+                .get(*offset..offset + magic.len())
+                .is_some_and(|bytes| bytes == *magic)
+        })
+        .map(|&(_, _, format)| format)
+}
+
+/// How binary files are scanned
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BinaryPolicy {
+    /// Don't scan them, they are reported as skipped
+    Skip,
+    /// Only scan strings of printable characters, like the `strings` command
+    #[default]
+    Strings,
+    /// Scan them the same way as text files
+    Full,
+}
+
+impl BinaryPolicy {
+    /// Name of the policy, as accepted by [`BinaryPolicy::from_str`]
+    pub fn name(self) -> &'static str {
+        match self {
+            BinaryPolicy::Skip => "skip",
+            BinaryPolicy::Strings => "strings",
+            BinaryPolicy::Full => "full",
+        }
+    }
+}
+
+impl fmt::Display for BinaryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for BinaryPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            BinaryPolicy::Skip,
+            BinaryPolicy::Strings,
+            BinaryPolicy::Full,
+        ]
+        .into_iter()
+        .find(|p| p.name().eq_ignore_ascii_case(s))
+        .ok_or_else(|| format!("expected `skip`, `strings` or `full`, found `{s}`"))
+    }
+}
+
+impl<'de> Deserialize<'de> for BinaryPolicy {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Why a file wasn't scanned
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum SkipReason {
+    /// The file is binary and [`BinaryPolicy::Skip`] is used
+    Binary { binary: Binary },
+    /// The file is larger than `max_filesize`
+    TooLarge { size: u64, max_filesize: u64 },
+    /// The file or directory was found while walking a directory, but couldn't be read,
+    /// e.g. a dangling symbolic link or a file without read permission
+    Unreadable { error: String },
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::Binary { binary } => write!(f, "binary file ({binary})"),
+            SkipReason::TooLarge { size, max_filesize } => write!(
+                f,
+                "{} is larger than {}",
+                human_bytes(*size),
+                human_bytes(*max_filesize)
+            ),
+            SkipReason::Unreadable { error } => write!(f, "unreadable ({error})"),
+        }
+    }
+}
+
+/// A file which wasn't scanned, along with the reason
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Skipped {
+    pub path: String,
+    #[serde(flatten)]
+    pub reason: SkipReason,
+}
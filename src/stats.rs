@@ -0,0 +1,119 @@
+/*!
+ * For summarizing a scan.
+ *
+ * [`ScanStats`] is returned by [`Identifier::identify_paths`](crate::Identifier::identify_paths)
+ * and passed to its progress callback while files are being scanned.
+ * It is also returned by [`Identifier::identify_with_stats`](crate::Identifier::identify_with_stats),
+ * which tells whether a [limit](crate::limits) stopped the scan early.
+ *
+ * # Example:
+ *
+ * ```rust
+ * use lemmeknow::{stats::ScanStats, Identifier};
+ * let result = Identifier::default().identify("hello@example.com");
+ *
+ * let mut stats = ScanStats::default();
+ * stats.add_matches(&result);
+ * assert_eq!(stats.matches, result.len());
+ * assert!(stats.tags["Email"] >= 1);
+ * ```
+ */
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Serialize, Serializer};
+
+use crate::limits::Truncation;
+use crate::sniff::Skipped;
+use crate::{Data, Match};
+
+/// Counters of a scan
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct ScanStats {
+    /// Number of files scanned
+    pub files: usize,
+    /// Number of bytes read from files
+    pub bytes: u64,
+    /// Number of strings extracted from files and identified
+    pub strings: usize,
+    /// Number of matches
+    pub matches: usize,
+    /// Number of matches having each tag
+    pub tags: BTreeMap<String, usize>,
+    /// Time taken by the scan, serialized as seconds
+    #[serde(serialize_with = "as_secs")]
+    pub elapsed: Duration,
+    /// Limit which stopped the scan early, `None` if everything was scanned
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub truncated: Option<Truncation>,
+    /// Files which weren't scanned, sorted by their path, see [`sniff`](crate::sniff)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<Skipped>,
+}
+
+impl ScanStats {
+    /// Count the matches and their tags
+    pub fn add_matches(&mut self, result: &[Match]) {
+        result.iter().for_each(|m| self.add_data(&m.data));
+    }
+
+    // a match of `data`
+    pub(crate) fn add_data(&mut self, data: &Data) {
+        self.matches += 1;
+        for tag in data.tags {
+            *self.tags.entry((*tag).to_owned()).or_default() += 1;
+        }
+    }
+}
+
+impl fmt::Display for ScanStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Scanned {} file(s), {}, {} string(s) in {:.2}s",
+            self.files,
+            human_bytes(self.bytes),
+            self.strings,
+            self.elapsed.as_secs_f64()
+        )?;
+        write!(f, "Found {} match(es)", self.matches)?;
+        // most common first
+        let mut tags: Vec<(&String, &usize)> = self.tags.iter().collect();
+        tags.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        for (tag, count) in tags {
+            write!(f, "\n  {tag}: {count}")?;
+        }
+        if !self.skipped.is_empty() {
+            write!(f, "\nSkipped {} file(s)", self.skipped.len())?;
+            for skipped in &self.skipped {
+                write!(f, "\n  {}: {}", skipped.path, skipped.reason)?;
+            }
+        }
+        if let Some(limit) = self.truncated {
+            write!(f, "\nStopped early, {limit} was reached")?;
+        }
+        Ok(())
+    }
+}
+
+/// Size in bytes, KiB, MiB or GiB, e.g. `1.50 MiB`
+pub fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.2} {}", UNITS[unit])
+}
+
+fn as_secs<S: Serializer>(elapsed: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(elapsed.as_secs_f64())
+}
@@ -0,0 +1,205 @@
+/*!
+ * For filtering patterns by their tags.
+ *
+ * Tags are always compared case-insensitively.
+ *
+ * [`TagMatch`] decides whether a pattern needs any or all of the included tags,
+ * while [`TagExpr`] allows arbitrary boolean expressions of tags using
+ * `&` (and), `|` (or), `!` (not) and parentheses, `&` binds tighter than `|`.
+ * Tags containing any of these, e.g. `Lat & Long`, must be quoted as `"Lat & Long"`.
+ *
+ * # Example:
+ *
+ * ```rust
+ * use lemmeknow::tags::TagExpr;
+ * let expr: TagExpr = "Credentials & !Bug Bounty".parse().unwrap();
+ *
+ * assert!(expr.matches(&["Credentials", "SSH Public Key"]));
+ * assert!(!expr.matches(&["credentials", "bug bounty"]));
+ *
+ * let expr: TagExpr = r#""Lat & Long" | Networking"#.parse().unwrap();
+ * assert!(expr.matches(&["Lat & Long"]));
+ * ```
+ */
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer};
+
+/// How included tags are matched
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMatch {
+    /// Pattern must have at least one of the tags
+    #[default]
+    Any,
+    /// Pattern must have every one of the tags
+    All,
+}
+
+impl TagMatch {
+    /// Returns `true` if `tags` satisfy the `included` tags.
+    ///
+    /// Always `true` if there are no `included` tags.
+    pub fn matches(self, included: &[String], tags: &[&str]) -> bool {
+        let has = |tag: &String| tags.iter().any(|t| t.eq_ignore_ascii_case(tag));
+        match self {
+            _ if included.is_empty() => true,
+            TagMatch::Any => included.iter().any(has),
+            TagMatch::All => included.iter().all(has),
+        }
+    }
+}
+
+impl FromStr for TagMatch {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "any" => Ok(TagMatch::Any),
+            "all" => Ok(TagMatch::All),
+            _ => Err(format!("expected `any` or `all`, found `{s}`")),
+        }
+    }
+}
+
+/// Boolean expression of tags
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagExpr {
+    Tag(String),
+    Not(Box<TagExpr>),
+    And(Box<TagExpr>, Box<TagExpr>),
+    Or(Box<TagExpr>, Box<TagExpr>),
+}
+
+impl TagExpr {
+    /// Returns `true` if `tags` satisfy this expression.
+    pub fn matches(&self, tags: &[&str]) -> bool {
+        match self {
+            TagExpr::Tag(tag) => tags.iter().any(|t| t.eq_ignore_ascii_case(tag)),
+            TagExpr::Not(expr) => !expr.matches(tags),
+            TagExpr::And(a, b) => a.matches(tags) && b.matches(tags),
+            TagExpr::Or(a, b) => a.matches(tags) || b.matches(tags),
+        }
+    }
+}
+
+impl fmt::Display for TagExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TagExpr::Tag(tag) if tag.contains(SPECIAL) => write!(f, "\"{tag}\""),
+            TagExpr::Tag(tag) => f.write_str(tag),
+            TagExpr::Not(expr) => write!(f, "!{expr}"),
+            TagExpr::And(a, b) => write!(f, "({a} & {b})"),
+            TagExpr::Or(a, b) => write!(f, "({a} | {b})"),
+        }
+    }
+}
+
+impl FromStr for TagExpr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { input: s, pos: 0 };
+        let expr = parser.or()?;
+        parser.skip_whitespace();
+        match parser.peek() {
+            None => Ok(expr),
+            Some(c) => Err(format!("unexpected `{c}` at {} in `{s}`", parser.pos)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TagExpr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+// characters which end an unquoted tag
+const SPECIAL: [char; 6] = ['&', '|', '!', '(', ')', '"'];
+
+// recursive descent parser, in order of precedence: `|`, `&`, `!`
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<TagExpr, String> {
+        let mut expr = self.and()?;
+        while self.eat('|') {
+            expr = TagExpr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<TagExpr, String> {
+        let mut expr = self.not()?;
+        while self.eat('&') {
+            expr = TagExpr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<TagExpr, String> {
+        if self.eat('!') {
+            Ok(TagExpr::Not(Box::new(self.not()?)))
+        } else if self.eat('(') {
+            let expr = self.or()?;
+            if self.eat(')') {
+                Ok(expr)
+            } else {
+                Err(format!("expected `)` at {} in `{}`", self.pos, self.input))
+            }
+        } else {
+            self.tag()
+        }
+    }
+
+    // tags may contain spaces, e.g. "Bug Bounty", and anything but `"` if quoted
+    fn tag(&mut self) -> Result<TagExpr, String> {
+        if self.eat('"') {
+            let rest = &self.input[self.pos..];
+            let Some(len) = rest.find('"') else {
+                return Err(format!(
+                    "expected `\"` at {} in `{}`",
+                    self.input.len(),
+                    self.input
+                ));
+            };
+            self.pos += len + 1;
+            return Ok(TagExpr::Tag(rest[..len].to_owned()));
+        }
+        let rest = &self.input[self.pos..];
+        let len = rest.find(SPECIAL).unwrap_or(rest.len());
+        let tag = rest[..len].trim_end();
+        if tag.is_empty() {
+            return Err(format!("expected tag at {} in `{}`", self.pos, self.input));
+        }
+        self.pos += len;
+        Ok(TagExpr::Tag(tag.to_owned()))
+    }
+}